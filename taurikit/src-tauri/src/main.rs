@@ -17,6 +17,9 @@ use serde_with::DurationSeconds;
 #[derive(Serialize, Deserialize)]
 struct MyConfig {
     base_path: String,
+    // named journal roots, base_path is used when empty
+    #[serde(default)]
+    sources: Vec<Source>,
     #[serde_as(as = "DurationSeconds<i64>")]
     start_ago: chrono::Duration,
 }
@@ -26,6 +29,7 @@ impl ::std::default::Default for MyConfig {
     fn default() -> Self {
         Self {
             base_path: "../../timespent/tests/days".into(),
+            sources: Vec::new(),
             start_ago: chrono::Duration::days(14),
         }
     }
@@ -35,14 +39,20 @@ impl ::std::default::Default for MyConfig {
 use std::sync::RwLock;
 
 use timespent::{
-    graph::ui::{Filter, Graph},
+    graph::scale::Scale,
+    graph::ui::{Filter, FilteredGraph, Graph},
+    graph::vega::VegaSpec,
     graph::x_segments::ScaleXSegments,
     graph::y_activities::YActivities,
-    loader,
+    loader::{self, Source},
+    timer::{self, RunningActivity},
 };
 
 pub struct StateContainer(pub RwLock<Graph>);
 
+// folder the timer keeps its state in, the same one as the command line
+pub struct WriteRoot(pub String);
+
 const CONFIG_NAME: &str = "config";
 const APP_NAME: &str = "timespent";
 
@@ -52,10 +62,19 @@ fn main() {
         confy::get_configuration_file_path(APP_NAME, CONFIG_NAME).unwrap(),
     );
     let cfg: MyConfig = confy::load(APP_NAME, Some(CONFIG_NAME)).unwrap();
-    let directory = &cfg.base_path;
-    println!("Loading data from {}", directory);
+    let activities = if cfg.sources.is_empty() {
+        let directory = &cfg.base_path;
+        println!("Loading data from {}", directory);
+
+        loader::load_from_filepath(directory)
+    } else {
+        cfg.sources.iter().for_each(|source| {
+            println!("Loading data from {} ({})", source.path, source.name);
+        });
 
-    let activities = loader::load_from_filepath(directory).expect("Failed to load data");
+        loader::load_from_sources(&cfg.sources)
+    }
+    .expect("Failed to load data");
     let mut graph = Graph::new(&activities);
 
     let mut default_filter = graph.all_filter.clone();
@@ -66,10 +85,16 @@ fn main() {
 
     tauri::Builder::default()
         .manage(state)
+        .manage(WriteRoot(
+            loader::write_source(&cfg.base_path, &cfg.sources).path,
+        ))
         .invoke_handler(tauri::generate_handler![
             get_graph,
             get_filter,
-            apply_filter
+            apply_filter,
+            query_graph,
+            get_vega_spec,
+            get_running
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -101,3 +126,36 @@ fn apply_filter(state: tauri::State<StateContainer>, filter: Filter) {
     let mut graph = state.0.write().unwrap();
     graph.apply_filter(&filter);
 }
+
+// query_graph only takes the read lock, the filter of the other windows is kept
+#[tauri::command]
+fn query_graph(state: tauri::State<StateContainer>, filter: Filter) -> FilteredGraph {
+    let graph = state.0.read().unwrap();
+
+    graph.query(&filter)
+}
+
+#[tauri::command]
+fn get_vega_spec(
+    state: tauri::State<StateContainer>,
+    spec: VegaSpec,
+    scale: Scale,
+) -> serde_json::Value {
+    let graph = state.0.read().unwrap();
+
+    graph.vega_lite(&spec, &scale)
+}
+
+#[tauri::command]
+fn get_running(write_root: tauri::State<WriteRoot>) -> Option<(RunningActivity, i64)> {
+    match timer::running(&write_root.0) {
+        Ok(running) => running.map(|running| {
+            let elapsed = running.elapsed_minutes(&chrono::Local::now().naive_local());
+            (running, elapsed)
+        }),
+        Err(err) => {
+            println!("can not read the running activity: {}", err);
+            None
+        }
+    }
+}
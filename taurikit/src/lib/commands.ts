@@ -1,11 +1,33 @@
 import type { Filter } from '../../timespent/bindings/Filter';
+import type { FilteredGraph } from '../../timespent/bindings/FilteredGraph';
 import type { ScaleXSegments } from '../../timespent/bindings/ScaleXSegments';
 import type { YActivities } from '../../timespent/bindings/YActivities';
+import type { RunningActivity } from '../../timespent/bindings/RunningActivity';
 
 import { invoke } from '@tauri-apps/api/tauri';
 
+// set VITE_TIMESPENT_API to the address of `timespent serve` to run in a browser
+const api: string | undefined = import.meta.env.VITE_TIMESPENT_API;
+
+async function call<T>(command: string, args: Record<string, unknown>): Promise<T> {
+	if (!api) {
+		return await invoke(command, args);
+	}
+
+	const res = await fetch(`${api}/api/${command}`, {
+		method: 'POST',
+		headers: { 'Content-Type': 'application/json' },
+		body: JSON.stringify(args)
+	});
+	if (!res.ok) {
+		throw new Error(await res.text());
+	}
+
+	return await res.json();
+}
+
 export async function getGraph(): Promise<[number, ScaleXSegments, YActivities]> {
-	const [scale_x_segments, y_activities]: [ScaleXSegments, YActivities] = await invoke(
+	const [scale_x_segments, y_activities]: [ScaleXSegments, YActivities] = await call(
 		'get_graph',
 		{}
 	);
@@ -16,15 +38,26 @@ export async function getGraph(): Promise<[number, ScaleXSegments, YActivities]>
 }
 
 export async function getFilter(): Promise<[ScaleXSegments, Filter, Filter]> {
-	const res: [ScaleXSegments, Filter, Filter] = await invoke('get_filter', {});
+	const res: [ScaleXSegments, Filter, Filter] = await call('get_filter', {});
 
 	return [res[0], res[1], res[2]];
 }
 
 export async function applyFilter(ftr: Filter): Promise<void> {
-	await invoke('apply_filter', {
+	await call('apply_filter', {
 		filter: ftr
 	});
 
 	return;
 }
+
+// queryGraph evaluates a filter without changing the one applied for the other views
+export async function queryGraph(ftr: Filter): Promise<FilteredGraph> {
+	return await call('query_graph', {
+		filter: ftr
+	});
+}
+
+export async function getRunning(): Promise<[RunningActivity, number] | null> {
+	return await call('get_running', {});
+}
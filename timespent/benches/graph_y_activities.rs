@@ -19,6 +19,8 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             description: "activity 1".to_string(),
             action: Action::Code,
             projects: ["tag1".to_string(), "tag2".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
         },
         Activity {
             start_datetime: NaiveDate::from_ymd_opt(2022, 7, 25)
@@ -32,6 +34,8 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             description: "activity 2".to_string(),
             action: Action::Review,
             projects: ["tag2".to_string(), "tag3".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
         },
         Activity {
             start_datetime: NaiveDate::from_ymd_opt(2022, 7, 26)
@@ -45,6 +49,8 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             description: "activity 3".to_string(),
             action: Action::Code,
             projects: ["tag1".to_string(), "tag2".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
         },
         Activity {
             start_datetime: NaiveDate::from_ymd_opt(2030, 7, 27)
@@ -58,6 +64,8 @@ pub fn criterion_benchmark(c: &mut Criterion) {
             description: "activity 5".to_string(),
             action: Action::Review,
             projects: ["tag2".to_string(), "tag3".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
         },
     ]);
 
@@ -71,6 +79,7 @@ pub fn criterion_benchmark(c: &mut Criterion) {
                 black_box(&activities),
                 black_box(&agg_activities.2),
                 black_box(&agg_activities.3),
+                black_box(&agg_activities.4),
                 black_box(&sxs),
             )
         })
@@ -1,4 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { Action } from "./Action";
 
-export type ActivitiesAggregate = [string, string, Array<Action>, Array<string>];
\ No newline at end of file
+export type ActivitiesAggregate = [string, string, Array<Action>, Array<string>, Array<string>];
\ No newline at end of file
@@ -1,4 +1,5 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { Action } from "./Action";
+import type { ActivityId } from "./ActivityId";
 
-export interface Activity { start_datetime: string, end_datetime: string, description: string, action: Action, projects: Array<string>, }
\ No newline at end of file
+export interface Activity { start_datetime: string, end_datetime: string, description: string, action: Action, projects: Array<string>, source: string, id: ActivityId | null, }
\ No newline at end of file
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ActivitiesAggregate } from "./ActivitiesAggregate";
+import type { Filter } from "./Filter";
+import type { ScaleXSegments } from "./ScaleXSegments";
+import type { YActivities } from "./YActivities";
+
+export interface FilteredGraph { filter: Filter, activities_aggregate: ActivitiesAggregate, per_scale_x_segments: ScaleXSegments, per_scale_y_activities: YActivities, }
\ No newline at end of file
@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Action } from "./Action";
+
+export interface RunningActivity { start_datetime: string, description: string, action: Action, projects: Array<string>, }
\ No newline at end of file
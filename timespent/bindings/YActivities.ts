@@ -2,4 +2,4 @@
 import type { Action } from "./Action";
 import type { Scale } from "./Scale";
 
-export interface YActivities { scale_total_minutes: Record<Scale, Array<bigint>>, scale_actions_total_minutes: Record<Scale, Record<Action, Array<bigint>>>, scale_projects_total_minutes: Record<Scale, Record<string, Array<bigint>>>, }
\ No newline at end of file
+export interface YActivities { scale_total_minutes: Record<Scale, Array<bigint>>, scale_actions_total_minutes: Record<Scale, Record<Action, Array<bigint>>>, scale_projects_total_minutes: Record<Scale, Record<string, Array<bigint>>>, scale_projects_split_minutes: Record<Scale, Record<string, Array<bigint>>>, scale_sources_total_minutes: Record<Scale, Record<string, Array<bigint>>>, }
\ No newline at end of file
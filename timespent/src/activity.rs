@@ -71,27 +71,135 @@ pub struct Activity {
     pub description: String,
     pub action: Action,
     pub projects: HashSet<String>,
+    // name of the journal root the activity was loaded from
+    #[serde(default)]
+    pub source: String,
+    // set when the activity was loaded from a file
+    #[serde(default)]
+    pub id: Option<ActivityId>,
 }
 
 impl Display for Activity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}h{}-{}h{}: [{}]{} {}",
-            self.start_datetime.format("%H"),
-            self.end_datetime.format("%M"),
-            self.end_datetime.format("%H"),
-            self.end_datetime.format("%M"),
-            self.action,
-            self.projects
+        let mut projects = self.projects.iter().collect::<Vec<&String>>();
+        projects.sort();
+
+        // unknown is written as no action tag, otherwise it would be read back as a project
+        let action = match self.action {
+            Action::Unknown => String::new(),
+            action => format!("[{}]", action),
+        };
+
+        let line = format!(
+            "{}-{}: {}{} {}",
+            self.start_datetime.format("%Hh%M"),
+            self.end_datetime.format("%Hh%M"),
+            action,
+            projects
                 .iter()
                 .map(|prj| "[".to_string() + prj + "]")
                 .collect::<String>(),
             self.description,
+        );
+
+        write!(f, "{}", line.trim_end())
+    }
+}
+
+// ActivityId points at an activity line in a day file of a source, the content hash lets it
+// be found again when lines above it were added or removed. only activities of day files get
+// an id, the lines of journal files and archives can not be written back
+#[derive(Eq, PartialEq, Debug, Hash, Clone, Deserialize, Serialize, TS)]
+#[ts(export)]
+pub struct ActivityId {
+    pub source: String,
+    pub date: NaiveDate,
+    pub line: usize,
+    pub hash: String,
+}
+
+impl ActivityId {
+    pub fn new(source: &str, date: NaiveDate, line: usize, activity: &Activity) -> ActivityId {
+        ActivityId {
+            source: source.to_string(),
+            date,
+            line,
+            hash: content_hash(&activity.to_string()),
+        }
+    }
+
+    pub fn matches(&self, activity: &Activity) -> bool {
+        self.hash == content_hash(&activity.to_string())
+    }
+}
+
+impl Display for ActivityId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}",
+            self.source,
+            self.date.format("%Y.%m.%d"),
+            self.line,
+            self.hash
+        )
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct ParseActivityIdError(String);
+
+impl Display for ParseActivityIdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is not an activity id like source:2022.05.27:3:0123456789abcdef",
+            self.0
         )
     }
 }
 
+impl std::error::Error for ParseActivityIdError {}
+
+impl FromStr for ActivityId {
+    type Err = ParseActivityIdError;
+
+    // the source comes first and may contain colons, like a windows path
+    fn from_str(input: &str) -> Result<ActivityId, Self::Err> {
+        let err = || ParseActivityIdError(input.to_string());
+
+        let mut parts = input.rsplitn(4, ':');
+        let hash = parts.next().ok_or_else(err)?;
+        let line = parts
+            .next()
+            .ok_or_else(err)?
+            .parse::<usize>()
+            .map_err(|_| err())?;
+        let date = NaiveDate::parse_from_str(parts.next().ok_or_else(err)?, "%Y.%m.%d")
+            .map_err(|_| err())?;
+        let source = parts.next().ok_or_else(err)?;
+        if hash.len() != 16 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(err());
+        }
+
+        Ok(ActivityId {
+            source: source.to_string(),
+            date,
+            line,
+            hash: hash.to_string(),
+        })
+    }
+}
+
+// fnv-1a, std's hasher is not guaranteed to stay the same between releases
+pub(crate) fn content_hash(content: &str) -> String {
+    let hash = content.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+
+    format!("{:016x}", hash)
+}
+
 #[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize, TS)]
 #[ts(export)]
 pub struct Activities(pub Vec<Activity>);
@@ -103,6 +211,7 @@ pub struct ActivitiesAggregate(
     pub NaiveDate,
     pub HashSet<Action>,
     pub HashSet<String>,
+    pub HashSet<String>,
 );
 
 impl Activities {
@@ -114,9 +223,11 @@ impl Activities {
                 NaiveDate::from_ymd_opt(0, 1, 1).unwrap(),
                 HashSet::new(),
                 HashSet::new(),
+                HashSet::new(),
             ),
             |mut act_agg: ActivitiesAggregate, activity| {
                 act_agg.2.insert(activity.action);
+                act_agg.4.insert(activity.source.clone());
                 let projects = act_agg
                     .3
                     .union(&activity.projects)
@@ -128,17 +239,21 @@ impl Activities {
                     activity.end_datetime.date().max(act_agg.1),
                     act_agg.2,
                     projects,
+                    act_agg.4,
                 )
             },
         )
     }
 
+    // filter keeps the activities of the period, an empty set of actions, projects or
+    // sources does not restrict on it so activities without any project can be kept
     pub fn filter(
         &self,
         start_date: &NaiveDate,
         end_date: &NaiveDate,
         actions: &HashSet<Action>,
         projects: &HashSet<String>,
+        sources: &HashSet<String>,
         search_text: &Option<String>,
     ) -> Activities {
         self.0
@@ -147,8 +262,10 @@ impl Activities {
             .filter(|activity| {
                 activity.start_datetime.date() >= *start_date
                     && activity.end_datetime.date() <= *end_date
-                    && actions.contains(&activity.action)
-                    && activity.projects.iter().any(|proj| projects.contains(proj))
+                    && (actions.is_empty() || actions.contains(&activity.action))
+                    && (projects.is_empty()
+                        || activity.projects.iter().any(|proj| projects.contains(proj)))
+                    && (sources.is_empty() || sources.contains(&activity.source))
             })
             .filter(|activity| {
                 if let Some(search) = search_text {
@@ -241,6 +358,8 @@ mod tests {
                 description: "description".to_string(),
                 action: Action::Code,
                 projects: ["tag2".to_string(), "tag3".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },
             Activity {
                 start_datetime: NaiveDate::from_ymd_opt(2022, 7, 25)
@@ -254,6 +373,8 @@ mod tests {
                 description: "description".to_string(),
                 action: Action::Review,
                 projects: ["tag2".to_string(), "tag3".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },
         ]);
 
@@ -264,6 +385,7 @@ mod tests {
                 NaiveDate::from_ymd_opt(2022, 7, 25).unwrap(),
                 [Action::Review, Action::Code].into(),
                 ["tag2".to_string(), "tag3".to_string()].into(),
+                ["work".to_string()].into(),
             )
         );
     }
@@ -283,6 +405,8 @@ mod tests {
                 description: "description".to_string(),
                 action: Action::Code,
                 projects: ["tag2".to_string(), "tag1".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },
             Activity {
                 start_datetime: NaiveDate::from_ymd_opt(2022, 7, 25)
@@ -296,6 +420,8 @@ mod tests {
                 description: "description".to_string(),
                 action: Action::Review,
                 projects: ["tag2".to_string(), "tag3".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },
         ]);
 
@@ -305,6 +431,7 @@ mod tests {
                 &NaiveDate::from_ymd_opt(2022, 7, 30).unwrap(),
                 &[Action::Code, Action::Review].into(),
                 &["tag2".to_string()].into(),
+                &["work".to_string()].into(),
                 &None,
             ),
             Activities(vec![Activity {
@@ -319,6 +446,8 @@ mod tests {
                 description: "description".to_string(),
                 action: Action::Review,
                 projects: ["tag2".to_string(), "tag3".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },])
         );
 
@@ -328,6 +457,7 @@ mod tests {
                 &NaiveDate::from_ymd_opt(2022, 7, 30).unwrap(),
                 &[Action::Code, Action::Review].into(),
                 &["tag2".to_string(), "tag3".to_string()].into(),
+                &["work".to_string()].into(),
                 &Some("desc".to_string()),
             ),
             Activities(vec![Activity {
@@ -342,6 +472,8 @@ mod tests {
                 description: "description".to_string(),
                 action: Action::Review,
                 projects: ["tag2".to_string(), "tag3".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },])
         );
 
@@ -351,10 +483,39 @@ mod tests {
                 &NaiveDate::from_ymd_opt(2022, 7, 30).unwrap(),
                 &[Action::Code, Action::Review].into(),
                 &["tag2".to_string(), "tag3".to_string()].into(),
+                &["work".to_string()].into(),
                 &Some("proto".to_string()),
             ),
             Activities(vec![])
         );
+
+        assert_eq!(
+            activities.filter(
+                &NaiveDate::from_ymd_opt(2022, 7, 23).unwrap(),
+                &NaiveDate::from_ymd_opt(2022, 7, 30).unwrap(),
+                &[Action::Code, Action::Review].into(),
+                &["tag2".to_string(), "tag3".to_string()].into(),
+                &["team".to_string()].into(),
+                &None,
+            ),
+            Activities(vec![])
+        );
+
+        // empty sets do not restrict, activities without a project are kept
+        let mut without_project = activities.0[0].clone();
+        without_project.projects.clear();
+        let activities = Activities(vec![without_project, activities.0[1].clone()]);
+        assert_eq!(
+            activities.filter(
+                &NaiveDate::from_ymd_opt(2022, 7, 22).unwrap(),
+                &NaiveDate::from_ymd_opt(2022, 7, 30).unwrap(),
+                &HashSet::new(),
+                &HashSet::new(),
+                &HashSet::new(),
+                &None,
+            ),
+            activities
+        );
     }
 
     #[test]
@@ -371,8 +532,75 @@ mod tests {
             description: "description".to_string(),
             action: Action::Code,
             projects: ["tag2".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
         };
 
         assert_eq!(act.to_string(), "12h00-13h00: [code][tag2] description");
+
+        let act = Activity {
+            start_datetime: NaiveDate::from_ymd_opt(2022, 7, 22)
+                .unwrap()
+                .and_hms_opt(9, 15, 0)
+                .unwrap(),
+            end_datetime: NaiveDate::from_ymd_opt(2022, 7, 22)
+                .unwrap()
+                .and_hms_opt(10, 45, 0)
+                .unwrap(),
+            description: "".to_string(),
+            action: Action::Unknown,
+            projects: ["tag2".to_string(), "tag1".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
+        };
+
+        assert_eq!(act.to_string(), "09h15-10h45: [tag1][tag2]");
+    }
+
+    #[test]
+    fn test_activity_id() {
+        let act = Activity {
+            start_datetime: NaiveDate::from_ymd_opt(2022, 7, 22)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+            end_datetime: NaiveDate::from_ymd_opt(2022, 7, 22)
+                .unwrap()
+                .and_hms_opt(13, 0, 0)
+                .unwrap(),
+            description: "description".to_string(),
+            action: Action::Code,
+            projects: ["tag2".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
+        };
+        let id = ActivityId::new(
+            "C:\\journal",
+            NaiveDate::from_ymd_opt(2022, 7, 22).unwrap(),
+            3,
+            &act,
+        );
+
+        assert!(id.matches(&act));
+        assert_eq!(
+            id.to_string(),
+            format!("C:\\journal:2022.07.22:3:{}", id.hash)
+        );
+        assert_eq!(ActivityId::from_str(&id.to_string()).unwrap(), id);
+        assert!(ActivityId::from_str("2022.07.22:3:0123456789abcdef").is_err());
+        assert_eq!(
+            ActivityId::from_str("work:2022.07.22:x:0123456789abcdef")
+                .unwrap_err()
+                .to_string(),
+            "work:2022.07.22:x:0123456789abcdef is not an activity id like source:2022.05.27:3:0123456789abcdef"
+        );
+
+        let mut other = act.clone();
+        other.description = "other".to_string();
+        assert!(!id.matches(&other));
+
+        other.description = "description".to_string();
+        other.source = "team".to_string();
+        assert!(id.matches(&other));
     }
 }
@@ -0,0 +1,233 @@
+use crate::activity::{Action, Activities, Activity};
+use chrono::{Local, NaiveDateTime, TimeZone};
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+// fields of a commit in the git log output, separated by the unit separator
+const LOG_FORMAT: &str = "--format=%H%x1f%an%x1f%ae%x1f%at%x1f%s";
+
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct Commit {
+    pub repository: String,
+    pub hash: String,
+    pub author: String,
+    pub email: String,
+    pub datetime: NaiveDateTime,
+    pub summary: String,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct ActivityCommits {
+    pub activity: Activity,
+    pub commits: Vec<Commit>,
+}
+
+// GitCorrelation attaches commits to the code activities open when they were made
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct GitCorrelation {
+    pub code_activities: Vec<ActivityCommits>,
+    // commits made while something else than code was logged
+    pub commits_during_other: Vec<ActivityCommits>,
+    pub code_without_commits: Vec<Activity>,
+    pub unlogged_commits: Vec<Commit>,
+}
+
+pub fn read_repositories_commits(
+    repositories: &[&str],
+    author: Option<&str>,
+) -> Result<Vec<Commit>> {
+    let mut commits = Vec::new();
+    for repository in repositories {
+        commits.extend(read_commits(repository, author)?);
+    }
+    commits.sort_by_key(|commit| commit.datetime);
+
+    Ok(commits)
+}
+
+// read_commits runs git log on a local repository, author filters like git log --author
+pub fn read_commits(repository: &str, author: Option<&str>) -> Result<Vec<Commit>> {
+    let mut cmd = Command::new("git");
+    cmd.args(["-C", repository, "log", "--all", LOG_FORMAT]);
+    if let Some(author) = author {
+        cmd.arg(format!("--author={}", author));
+    }
+
+    let output = cmd.output()?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "git log failed in {}: {}",
+            repository,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+
+    parse_log(repository, &String::from_utf8_lossy(&output.stdout), &Local)
+}
+
+fn parse_log<Tz: TimeZone>(repository: &str, log: &str, tz: &Tz) -> Result<Vec<Commit>> {
+    log.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let fields = line.split('\x1f').collect::<Vec<&str>>();
+            if fields.len() != 5 {
+                return Err(eyre!("unexpected git log line {}", line));
+            }
+            let timestamp = fields[3].parse::<i64>()?;
+
+            Ok(Commit {
+                repository: repository.to_string(),
+                hash: fields[0].to_string(),
+                author: fields[1].to_string(),
+                email: fields[2].to_string(),
+                datetime: tz
+                    .timestamp_opt(timestamp, 0)
+                    .single()
+                    .ok_or_else(|| eyre!("invalid timestamp {}", timestamp))?
+                    .naive_local(),
+                summary: fields[4].to_string(),
+            })
+        })
+        .collect()
+}
+
+pub fn correlate(activities: &Activities, commits: &[Commit]) -> GitCorrelation {
+    let mut code_activities = Vec::new();
+    let mut commits_during_other: Vec<ActivityCommits> = Vec::new();
+    let mut unlogged_commits = Vec::new();
+
+    activities
+        .0
+        .iter()
+        .filter(|activity| activity.action == Action::Code)
+        .for_each(|activity| {
+            code_activities.push(ActivityCommits {
+                activity: activity.clone(),
+                commits: Vec::new(),
+            })
+        });
+
+    for commit in commits {
+        let open = |activity: &Activity| {
+            activity.start_datetime <= commit.datetime && commit.datetime < activity.end_datetime
+        };
+
+        if let Some(code) = code_activities.iter_mut().find(|code| open(&code.activity)) {
+            code.commits.push(commit.clone());
+            continue;
+        }
+
+        match activities.0.iter().find(|activity| open(activity)) {
+            Some(activity) => {
+                match commits_during_other
+                    .iter_mut()
+                    .find(|other| other.activity == *activity)
+                {
+                    Some(other) => other.commits.push(commit.clone()),
+                    None => commits_during_other.push(ActivityCommits {
+                        activity: activity.clone(),
+                        commits: vec![commit.clone()],
+                    }),
+                }
+            }
+            None => unlogged_commits.push(commit.clone()),
+        }
+    }
+
+    let code_without_commits = code_activities
+        .iter()
+        .filter(|code| code.commits.is_empty())
+        .map(|code| code.activity.clone())
+        .collect();
+
+    GitCorrelation {
+        code_activities,
+        commits_during_other,
+        code_without_commits,
+        unlogged_commits,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, Utc};
+
+    fn datetime(h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2022, 5, 27)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_log() {
+        let log = "a1b2\x1fjb\x1fjb@mail.com\x1f1653642000\x1ffix loader\n\n";
+        let commits = parse_log("timespent", log, &Utc).unwrap();
+
+        assert_eq!(
+            commits,
+            vec![Commit {
+                repository: "timespent".to_string(),
+                hash: "a1b2".to_string(),
+                author: "jb".to_string(),
+                email: "jb@mail.com".to_string(),
+                datetime: datetime(9, 0),
+                summary: "fix loader".to_string(),
+            }]
+        );
+        assert!(parse_log("timespent", "a1b2\x1fjb", &Utc).is_err());
+    }
+
+    #[test]
+    fn test_correlate() {
+        let activity = |start: NaiveDateTime, end: NaiveDateTime, action: Action| Activity {
+            start_datetime: start,
+            end_datetime: end,
+            description: "".to_string(),
+            action,
+            projects: ["rust".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
+        };
+        let commit = |datetime: NaiveDateTime| Commit {
+            repository: "timespent".to_string(),
+            hash: datetime.to_string(),
+            author: "jb".to_string(),
+            email: "jb@mail.com".to_string(),
+            datetime,
+            summary: "".to_string(),
+        };
+
+        let activities = Activities(vec![
+            activity(datetime(9, 0), datetime(10, 0), Action::Code),
+            activity(datetime(10, 0), datetime(11, 0), Action::Meeting),
+            activity(datetime(11, 0), datetime(12, 0), Action::Code),
+        ]);
+        let commits = vec![
+            commit(datetime(9, 30)),
+            commit(datetime(10, 0)),
+            commit(datetime(13, 0)),
+        ];
+
+        let correlation = correlate(&activities, &commits);
+
+        assert_eq!(
+            correlation.code_activities[0].commits,
+            vec![commits[0].clone()]
+        );
+        assert_eq!(
+            correlation.commits_during_other,
+            vec![ActivityCommits {
+                activity: activities.0[1].clone(),
+                commits: vec![commits[1].clone()],
+            }]
+        );
+        assert_eq!(
+            correlation.code_without_commits,
+            vec![activities.0[2].clone()]
+        );
+        assert_eq!(correlation.unlogged_commits, vec![commits[2].clone()]);
+    }
+}
@@ -0,0 +1,267 @@
+use crate::activity::{Action, Activities, Activity};
+use crate::import::ical::{read_events, CalendarEvent};
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::BufReader;
+
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct DurationMismatch {
+    pub event: CalendarEvent,
+    pub activity: Activity,
+    // logged minutes minus calendar minutes
+    pub difference_minutes: i64,
+}
+
+// Reconciliation compares the logged meetings with the calendar events of the same period
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct Reconciliation {
+    pub unlogged_events: Vec<CalendarEvent>,
+    pub unscheduled_meetings: Vec<Activity>,
+    pub duration_mismatches: Vec<DurationMismatch>,
+}
+
+// reconcile_ics_file compares the meetings and the calendar events between from and to
+pub fn reconcile_ics_file(
+    activities: &Activities,
+    path: &str,
+    from: &NaiveDate,
+    to: &NaiveDate,
+    tolerance: Duration,
+) -> Result<Reconciliation> {
+    let events = read_events(BufReader::new(File::open(path)?), &Local, from, to)?;
+    let meetings = activities.filter(
+        from,
+        to,
+        &[Action::Meeting].into(),
+        &HashSet::new(),
+        &HashSet::new(),
+        &None,
+    );
+
+    Ok(reconcile(&meetings, &events, tolerance))
+}
+
+// reconcile pairs each calendar event with the logged meeting overlapping it the most,
+// pairs whose durations differ by more than the tolerance are reported as mismatches
+pub fn reconcile(
+    activities: &Activities,
+    events: &[CalendarEvent],
+    tolerance: Duration,
+) -> Reconciliation {
+    let mut meetings = activities
+        .0
+        .iter()
+        .filter(|activity| activity.action == Action::Meeting)
+        .map(Some)
+        .collect::<Vec<Option<&Activity>>>();
+
+    let mut unlogged_events = Vec::new();
+    let mut duration_mismatches = Vec::new();
+
+    for event in events {
+        let best = meetings
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, meeting)| meeting.map(|meeting| (idx, meeting)))
+            .map(|(idx, meeting)| {
+                (
+                    idx,
+                    overlap(
+                        (&event.start_datetime, &event.end_datetime),
+                        (&meeting.start_datetime, &meeting.end_datetime),
+                    ),
+                )
+            })
+            .filter(|(_, overlap)| *overlap > Duration::zero())
+            .max_by_key(|(_, overlap)| *overlap)
+            .map(|(idx, _)| idx);
+
+        let idx = match best {
+            Some(idx) => idx,
+            None => {
+                unlogged_events.push(event.clone());
+                continue;
+            }
+        };
+
+        let meeting = meetings[idx].take().unwrap();
+        let difference = (meeting.end_datetime - meeting.start_datetime)
+            - (event.end_datetime - event.start_datetime);
+        if difference > tolerance || difference < -tolerance {
+            duration_mismatches.push(DurationMismatch {
+                event: event.clone(),
+                activity: meeting.clone(),
+                difference_minutes: difference.num_minutes(),
+            });
+        }
+    }
+
+    Reconciliation {
+        unlogged_events,
+        unscheduled_meetings: meetings.into_iter().flatten().cloned().collect(),
+        duration_mismatches,
+    }
+}
+
+fn overlap(a: (&NaiveDateTime, &NaiveDateTime), b: (&NaiveDateTime, &NaiveDateTime)) -> Duration {
+    *a.1.min(b.1) - *a.0.max(b.0)
+}
+
+impl Display for Reconciliation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "calendar events not logged:")?;
+        for event in &self.unlogged_events {
+            writeln!(
+                f,
+                "  {} {}-{} {}",
+                event.start_datetime.format("%Y.%m.%d"),
+                event.start_datetime.format("%Hh%M"),
+                event.end_datetime.format("%Hh%M"),
+                event.summary
+            )?;
+        }
+
+        writeln!(f, "logged meetings not in the calendar:")?;
+        for activity in &self.unscheduled_meetings {
+            writeln!(
+                f,
+                "  {} {}",
+                activity.start_datetime.format("%Y.%m.%d"),
+                activity
+            )?;
+        }
+
+        writeln!(f, "duration differences:")?;
+        for mismatch in &self.duration_mismatches {
+            writeln!(
+                f,
+                "  {} {} {:+}min",
+                mismatch.activity.start_datetime.format("%Y.%m.%d"),
+                mismatch.activity,
+                mismatch.difference_minutes
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_dir;
+    use std::fs::write;
+
+    fn datetime(d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2022, 5, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    fn meeting(start: NaiveDateTime, end: NaiveDateTime, description: &str) -> Activity {
+        Activity {
+            start_datetime: start,
+            end_datetime: end,
+            description: description.to_string(),
+            action: Action::Meeting,
+            projects: ["management".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
+        }
+    }
+
+    fn event(start: NaiveDateTime, end: NaiveDateTime, summary: &str) -> CalendarEvent {
+        CalendarEvent {
+            uid: summary.to_string(),
+            summary: summary.to_string(),
+            start_datetime: start,
+            end_datetime: end,
+            categories: vec![],
+        }
+    }
+
+    #[test]
+    fn test_reconcile() {
+        let activities = Activities(vec![
+            meeting(datetime(27, 15, 45), datetime(27, 17, 30), "with MT"),
+            meeting(datetime(27, 17, 30), datetime(27, 18, 0), "with jb"),
+            meeting(datetime(30, 9, 0), datetime(30, 9, 30), "coffee"),
+            Activity {
+                action: Action::Code,
+                ..meeting(datetime(30, 10, 0), datetime(30, 11, 0), "loader")
+            },
+        ]);
+        let events = vec![
+            event(datetime(27, 16, 0), datetime(27, 17, 0), "1:1 MT"),
+            event(datetime(27, 17, 30), datetime(27, 18, 0), "weekly"),
+            event(datetime(30, 10, 0), datetime(30, 11, 0), "planning"),
+        ];
+
+        let reconciliation = reconcile(&activities, &events, Duration::minutes(15));
+
+        assert_eq!(reconciliation.unlogged_events, vec![events[2].clone()]);
+        assert_eq!(
+            reconciliation.unscheduled_meetings,
+            vec![activities.0[2].clone()]
+        );
+        assert_eq!(
+            reconciliation.duration_mismatches,
+            vec![DurationMismatch {
+                event: events[0].clone(),
+                activity: activities.0[0].clone(),
+                difference_minutes: 45,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_ics_file() {
+        let dir = test_dir("reconcile");
+        let path = format!("{}/calendar.ics", dir.path());
+        write(
+            &path,
+            "BEGIN:VCALENDAR\r
+BEGIN:VEVENT\r
+UID:weekly@timespent\r
+SUMMARY:weekly\r
+DTSTART:20220527T173000\r
+DTEND:20220527T180000\r
+RRULE:FREQ=WEEKLY\r
+END:VEVENT\r
+END:VCALENDAR\r
+",
+        )
+        .unwrap();
+        let activities = Activities(vec![
+            meeting(datetime(20, 17, 30), datetime(20, 18, 0), "weekly"),
+            meeting(datetime(27, 17, 30), datetime(27, 18, 0), "weekly"),
+        ]);
+
+        let reconciliation = reconcile_ics_file(
+            &activities,
+            &path,
+            &NaiveDate::from_ymd_opt(2022, 5, 23).unwrap(),
+            &NaiveDate::from_ymd_opt(2022, 6, 5).unwrap(),
+            Duration::minutes(15),
+        )
+        .unwrap();
+
+        assert_eq!(
+            reconciliation.unlogged_events,
+            vec![CalendarEvent {
+                uid: "weekly@timespent".to_string(),
+                ..event(
+                    datetime(27, 17, 30) + Duration::weeks(1),
+                    datetime(27, 18, 0) + Duration::weeks(1),
+                    "weekly"
+                )
+            }]
+        );
+        assert!(reconciliation.unscheduled_meetings.is_empty());
+    }
+}
@@ -88,6 +88,8 @@ fn gen_day(day: &chrono::NaiveDate, actions: &[&Action], projects: &[&str]) -> A
             description: "description".to_string(),
             action: *selected_action,
             projects: HashSet::from(selected_project),
+            source: String::new(),
+            id: None,
         };
 
         activities.push(activity);
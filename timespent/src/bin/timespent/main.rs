@@ -0,0 +1,611 @@
+use chrono::{Local, NaiveDate};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{read_to_string, File};
+use std::io::{stdout, Write};
+use std::path::Path;
+use std::process::exit;
+use timespent::activity::{Action, Activities};
+#[cfg(feature = "arrow")]
+use timespent::export::arrow;
+use timespent::export::csv::{export_csv, Column};
+use timespent::export::report::{duration, Report, ReportFormat};
+use timespent::export::standup::Standup;
+use timespent::export::timesheet::{Rounding, RoundingRule, RoundingTarget, Timesheet};
+use timespent::export::{ical, json, ActivityRecord};
+use timespent::graph::scale::Scale;
+use timespent::graph::ui::Filter;
+use timespent::lint::{format_dir, lint_dir};
+use timespent::loader::{self, Source};
+use timespent::parser::parse_activity;
+use timespent::shorthand::parse_shorthand;
+use timespent::timer;
+use timespent::writer::insert_activity;
+
+mod serve;
+#[cfg(feature = "tui")]
+mod tui;
+
+// same file and fields as the config of the tauri app
+const CONFIG_NAME: &str = "config";
+const APP_NAME: &str = "timespent";
+
+#[derive(Default, Serialize, Deserialize)]
+struct Config {
+    base_path: String,
+    #[serde(default)]
+    sources: Vec<Source>,
+}
+
+#[derive(Parser)]
+#[command(
+    name = "timespent",
+    about = "query the activities of a timespent journal"
+)]
+struct Cli {
+    /// journal folder, overrides the base_path of the config
+    #[arg(long, global = true)]
+    base_path: Option<String>,
+    /// print json instead of tables
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// time per action and per project, compared with the previous period
+    Summary(FilterArgs),
+    /// markdown or html report of the period
+    Report {
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: ReportFormatArg,
+        /// tinytemplate file replacing the default layout
+        #[arg(long)]
+        template: Option<String>,
+    },
+    /// list the matching activities
+    Query(FilterArgs),
+    /// what was done on the previous working day
+    Standup,
+    /// report unparsable, overlapping and unordered lines of the day files
+    Lint,
+    /// rewrite the activity lines of the day files in the canonical format
+    Fmt {
+        /// only list the files that would change
+        #[arg(long)]
+        check: bool,
+    },
+    /// export the matching activities
+    Export {
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+        /// written to stdout when not set
+        #[arg(long, short)]
+        output: Option<String>,
+        /// timesheet of the day, week or month containing --to
+        #[arg(long, value_enum, default_value = "week")]
+        period: PeriodArg,
+        /// timesheet rounding increment in minutes
+        #[arg(long, default_value_t = 15)]
+        increment: i64,
+        #[arg(long, value_enum, default_value = "up")]
+        rounding: RoundingArg,
+        /// round each entry before summing, or each cell of the timesheet
+        #[arg(long, value_enum, default_value = "cell")]
+        round_per: RoundingTargetArg,
+    },
+    /// add an activity line like "09h00-10h00: [code][rust] fixed loader",
+    /// or a shorthand like "30m code rust fixed loader" or "14h-15h30 meeting planning"
+    Add {
+        #[arg(required = true)]
+        words: Vec<String>,
+        /// day of the activity, today when not set
+        #[arg(long, value_parser = parse_date)]
+        date: Option<NaiveDate>,
+    },
+    /// start an activity like "[code][rust] fixing parser", stopping the running one
+    Start {
+        #[arg(required = true)]
+        words: Vec<String>,
+    },
+    /// write the running activity in its day file
+    Stop,
+    /// show the running activity and for how long it has been running
+    Status,
+    /// interactive dashboard of the time per scale, with a filter and the matching activities
+    /// (needs a build with the tui feature: cargo install timespent --features tui)
+    Tui,
+    /// serve the commands of the app and the exports as a local json api
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+        /// origin allowed to call the api from a browser, like http://localhost:1420,
+        /// no other website can read the replies
+        #[arg(long)]
+        allow_origin: Option<String>,
+    },
+}
+
+#[derive(Args)]
+struct FilterArgs {
+    /// first day, the first logged day when not set
+    #[arg(long, value_parser = parse_date)]
+    from: Option<NaiveDate>,
+    /// last day, the last logged day when not set
+    #[arg(long, value_parser = parse_date)]
+    to: Option<NaiveDate>,
+    #[arg(long = "action")]
+    actions: Vec<String>,
+    #[arg(long = "project")]
+    projects: Vec<String>,
+    #[arg(long = "source")]
+    sources: Vec<String>,
+    /// text the description must contain
+    #[arg(long)]
+    search: Option<String>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormatArg {
+    Markdown,
+    Html,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PeriodArg {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RoundingArg {
+    Up,
+    Down,
+    Nearest,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum RoundingTargetArg {
+    Entry,
+    Cell,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Ndjson,
+    Ics,
+    #[cfg(feature = "arrow")]
+    Arrow,
+    #[cfg(feature = "arrow")]
+    Parquet,
+    Timesheet,
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    match run(&cli) {
+        Ok(true) => {}
+        Ok(false) => exit(1),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            exit(2);
+        }
+    }
+}
+
+// run returns false when the command found problems, like lint issues
+fn run(cli: &Cli) -> Result<bool> {
+    let config = load_config(&cli.base_path)?;
+
+    match &cli.command {
+        Command::Summary(args) => {
+            let activities = load(&config)?;
+            let report = Report::new(&activities, &args.filter(&activities)?);
+
+            if cli.json {
+                print_json(&report)?;
+                return Ok(true);
+            }
+
+            println!(
+                "{} - {}: {} ({} compared to {} - {})\n",
+                report.start_date,
+                report.end_date,
+                report.total.duration,
+                report.total.difference,
+                report.previous_start_date,
+                report.previous_end_date
+            );
+            for (title, lines) in [("action", &report.actions), ("project", &report.projects)] {
+                print_table(
+                    &[title, "time", "previous", "difference"],
+                    lines
+                        .iter()
+                        .map(|line| {
+                            vec![
+                                line.name.clone(),
+                                line.duration.clone(),
+                                line.previous_duration.clone(),
+                                line.difference.clone(),
+                            ]
+                        })
+                        .collect(),
+                );
+                println!();
+            }
+        }
+        Command::Report {
+            filter,
+            format,
+            template,
+        } => {
+            let activities = load(&config)?;
+            let report = Report::new(&activities, &filter.filter(&activities)?);
+            let template = template.as_ref().map(read_to_string).transpose()?;
+            let format = match format {
+                ReportFormatArg::Markdown => ReportFormat::Markdown,
+                ReportFormatArg::Html => ReportFormat::Html,
+            };
+
+            print!("{}", report.render(format, template.as_deref())?);
+        }
+        Command::Query(args) => {
+            let activities = load(&config)?;
+            let filtered = args.filter(&activities)?.apply(&activities);
+
+            if cli.json {
+                print_json(
+                    &filtered
+                        .0
+                        .iter()
+                        .map(ActivityRecord::from)
+                        .collect::<Vec<ActivityRecord>>(),
+                )?;
+                return Ok(true);
+            }
+
+            print_table(
+                &[
+                    "date",
+                    "time",
+                    "action",
+                    "projects",
+                    "description",
+                    "source",
+                ],
+                filtered
+                    .0
+                    .iter()
+                    .map(ActivityRecord::from)
+                    .map(|record| {
+                        vec![
+                            record.start[..10].to_string(),
+                            format!("{}-{}", &record.start[11..16], &record.end[11..16]),
+                            record.action,
+                            record.projects.join(","),
+                            record.description,
+                            record.source,
+                        ]
+                    })
+                    .collect(),
+            );
+        }
+        Command::Standup => {
+            let activities = load(&config)?;
+            match Standup::new(&activities, &Local::now().date_naive()) {
+                Some(standup) => print!("{}", standup),
+                None => println!("nothing logged before today"),
+            }
+        }
+        Command::Lint => {
+            let mut issues = Vec::new();
+            for root in config.day_file_roots() {
+                issues.extend(lint_dir(&root)?);
+            }
+
+            if cli.json {
+                print_json(&issues)?;
+            } else {
+                for issue in &issues {
+                    println!("{}:{}: {}", issue.path, issue.line, issue.message);
+                }
+            }
+
+            return Ok(issues.is_empty());
+        }
+        Command::Fmt { check } => {
+            let mut changed = Vec::new();
+            for root in config.day_file_roots() {
+                changed.extend(format_dir(&root, *check)?);
+            }
+
+            if cli.json {
+                print_json(&changed)?;
+            } else {
+                for path in &changed {
+                    println!("{}", path);
+                }
+            }
+
+            return Ok(!*check || changed.is_empty());
+        }
+        Command::Export {
+            filter,
+            format,
+            output,
+            period,
+            increment,
+            rounding,
+            round_per,
+        } => {
+            let activities = load(&config)?;
+            let filter = filter.filter(&activities)?;
+            let filtered = filter.apply(&activities);
+
+            let writer: Box<dyn Write + Send> = match output {
+                Some(path) => Box::new(File::create(path)?),
+                None => Box::new(stdout()),
+            };
+
+            match format {
+                ExportFormat::Csv => {
+                    export_csv(writer, &filtered, &Column::iterator().collect::<Vec<_>>())?
+                }
+                ExportFormat::Ndjson => json::export_ndjson(writer, &filtered)?,
+                ExportFormat::Ics => ical::export_ics(writer, &filtered)?,
+                #[cfg(feature = "arrow")]
+                ExportFormat::Arrow => arrow::export_arrow(writer, &filtered)?,
+                #[cfg(feature = "arrow")]
+                ExportFormat::Parquet => arrow::export_parquet(writer, &filtered)?,
+                ExportFormat::Timesheet => Timesheet::new(
+                    &filtered,
+                    &match period {
+                        PeriodArg::Day => Scale::Day,
+                        PeriodArg::Week => Scale::Week,
+                        PeriodArg::Month => Scale::Month,
+                    },
+                    &filter.max_date,
+                    &RoundingRule {
+                        increment_minutes: *increment,
+                        rounding: match rounding {
+                            RoundingArg::Up => Rounding::Up,
+                            RoundingArg::Down => Rounding::Down,
+                            RoundingArg::Nearest => Rounding::Nearest,
+                        },
+                        target: match round_per {
+                            RoundingTargetArg::Entry => RoundingTarget::Entry,
+                            RoundingTargetArg::Cell => RoundingTarget::Cell,
+                        },
+                    },
+                )?
+                .to_csv(writer)?,
+            }
+        }
+        Command::Add { words, date } => {
+            let now = Local::now().naive_local();
+            let date = date.unwrap_or_else(|| now.date());
+            let line = words.join(" ");
+
+            // the shorthand tells known projects from description words
+            let activity = match parse_activity(&date.format("%Y.%m.%d").to_string(), &line) {
+                Ok((_, activity)) => activity,
+                Err(_) => {
+                    let known = match load(&config) {
+                        Ok(activities) => activities.aggregate_all(),
+                        Err(err) => {
+                            eprintln!("can not load the known projects: {}", err);
+                            Activities(Vec::new()).aggregate_all()
+                        }
+                    };
+                    parse_shorthand(&line, &known, &date, &now)?
+                }
+            };
+            let id = insert_activity(&config.write_source(), &activity)?;
+
+            if cli.json {
+                print_json(&id)?;
+            } else {
+                println!("{} {}", id, activity);
+            }
+        }
+        Command::Start { words } => {
+            let (running, stopped) = timer::start(
+                &config.write_source(),
+                &words.join(" "),
+                &Local::now().naive_local(),
+            )?;
+
+            if cli.json {
+                print_json(&running)?;
+            } else {
+                if let Some(ids) = stopped {
+                    ids.iter().for_each(|id| println!("stopped {}", id));
+                }
+                println!("{}", running);
+            }
+        }
+        Command::Stop => match timer::stop(&config.write_source(), &Local::now().naive_local())? {
+            Some(ids) if cli.json => print_json(&ids)?,
+            Some(ids) if ids.is_empty() => println!("stopped, nothing written under a minute"),
+            Some(ids) => ids.iter().for_each(|id| println!("stopped {}", id)),
+            None => {
+                eprintln!("nothing is running");
+                return Ok(false);
+            }
+        },
+        Command::Status => {
+            let running = timer::running(&config.write_source().path)?;
+            let elapsed = running
+                .as_ref()
+                .map(|running| running.elapsed_minutes(&Local::now().naive_local()));
+
+            if cli.json {
+                print_json(&running.map(|running| (running, elapsed)))?;
+                return Ok(true);
+            }
+
+            match (running, elapsed) {
+                (Some(running), Some(elapsed)) => {
+                    println!("{} ({})", running, duration(elapsed))
+                }
+                _ => println!("nothing is running"),
+            }
+        }
+        #[cfg(feature = "tui")]
+        Command::Tui => tui::run(&load(&config)?)?,
+        #[cfg(not(feature = "tui"))]
+        Command::Tui => {
+            return Err(eyre!(
+                "timespent was built without the tui feature, install it with --features tui"
+            ))
+        }
+        Command::Serve { addr, allow_origin } => serve::run(
+            &load(&config)?,
+            &config.write_source().path,
+            addr,
+            allow_origin.clone(),
+        )?,
+    }
+
+    Ok(true)
+}
+
+impl Config {
+    fn write_source(&self) -> Source {
+        loader::write_source(&self.base_path, &self.sources)
+    }
+
+    // day_file_roots are the folders of the sources, or base_path without sources,
+    // journal files and archives are not written to so lint and fmt leave them out
+    fn day_file_roots(&self) -> Vec<String> {
+        if self.sources.is_empty() {
+            return vec![self.base_path.clone()];
+        }
+
+        let mut roots: Vec<String> = Vec::new();
+        for source in &self.sources {
+            if Path::new(&source.path).is_dir() && !roots.contains(&source.path) {
+                roots.push(source.path.clone());
+            }
+        }
+
+        roots
+    }
+}
+
+// load_config reads the config of the app without creating it, so a missing file
+// does not get written with only the fields known here
+fn load_config(base_path: &Option<String>) -> Result<Config> {
+    if let Some(base_path) = base_path {
+        return Ok(Config {
+            base_path: base_path.clone(),
+            sources: Vec::new(),
+        });
+    }
+
+    let path = confy::get_configuration_file_path(APP_NAME, CONFIG_NAME)?;
+    if !path.exists() {
+        return Err(eyre!(
+            "no config found at {}, use --base-path",
+            path.display()
+        ));
+    }
+
+    Ok(confy::load(APP_NAME, Some(CONFIG_NAME))?)
+}
+
+fn load(config: &Config) -> Result<Activities> {
+    let activities = if config.sources.is_empty() {
+        loader::load_from_filepath(&config.base_path)?
+    } else {
+        loader::load_from_sources(&config.sources)?
+    };
+
+    if activities.0.is_empty() {
+        return Err(eyre!("no activities found"));
+    }
+
+    Ok(activities)
+}
+
+impl FilterArgs {
+    // filter starts from everything that was loaded, each option narrows it down,
+    // options left empty keep every activity, the ones without a project too
+    fn filter(&self, activities: &Activities) -> Result<Filter> {
+        let agg = activities.aggregate_all();
+
+        Ok(Filter {
+            min_date: self.from.unwrap_or(agg.0),
+            max_date: self.to.unwrap_or(agg.1),
+            actions: self
+                .actions
+                .iter()
+                .map(|action| parse_action(action))
+                .collect::<Result<HashSet<Action>>>()?,
+            projects: self.projects.iter().cloned().collect(),
+            sources: self.sources.iter().cloned().collect(),
+            description: self.search.clone(),
+        })
+    }
+}
+
+fn parse_action(input: &str) -> Result<Action> {
+    match input {
+        "unknown" => Ok(Action::Unknown),
+        _ => input
+            .parse::<Action>()
+            .map_err(|_| eyre!("unknown action {}", input)),
+    }
+}
+
+fn parse_date(input: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(input, "%Y.%m.%d"))
+        .map_err(|_| format!("{} is not a date like 2022-05-27", input))
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+
+    Ok(())
+}
+
+// print_table aligns the columns on the widest value
+fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
+    let mut widths = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect::<Vec<usize>>();
+    for row in &rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    let line = |values: Vec<&str>| {
+        values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:width$}", value, width = width))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    println!("{}", line(headers.to_vec()));
+    for row in &rows {
+        println!("{}", line(row.iter().map(|value| value.as_str()).collect()));
+    }
+}
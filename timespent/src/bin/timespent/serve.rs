@@ -0,0 +1,511 @@
+use chrono::Datelike;
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use timespent::activity::Activities;
+#[cfg(feature = "arrow")]
+use timespent::export::arrow;
+use timespent::export::csv::{export_csv, Column};
+use timespent::export::{ical, json, ActivityRecord};
+use timespent::graph::scale::Scale;
+use timespent::graph::ui::{Filter, Graph};
+use timespent::graph::vega::VegaSpec;
+use timespent::timer;
+use tiny_http::{Header, Method, Response, Server};
+
+// the commands are posted to /api/<name> with the arguments given to the tauri invoke,
+// so the svelte app only changes its transport
+#[derive(Deserialize)]
+struct ApplyFilterArgs {
+    filter: Filter,
+}
+
+// without a filter, the filter applied with apply_filter is used
+#[derive(Deserialize)]
+struct QueryArgs {
+    filter: Option<Filter>,
+}
+
+// filters build one segment per day, the range is capped to a century of days
+// and to the years chrono can move around without overflowing
+const MAX_FILTER_DAYS: i64 = 100 * 366;
+const MIN_FILTER_YEAR: i32 = 1;
+const MAX_FILTER_YEAR: i32 = 9999;
+
+#[derive(Deserialize)]
+struct VegaSpecArgs {
+    spec: VegaSpec,
+    scale: Scale,
+}
+
+struct Reply {
+    status: u16,
+    content_type: &'static str,
+    body: Vec<u8>,
+    // set when the request comes from the origin allowed to read the replies
+    allow_origin: Option<String>,
+}
+
+impl Reply {
+    fn json<T: Serialize>(value: &T) -> Result<Reply> {
+        Ok(Reply {
+            status: 200,
+            content_type: "application/json",
+            body: serde_json::to_vec(value)?,
+            allow_origin: None,
+        })
+    }
+
+    fn error(status: u16, message: &str) -> Reply {
+        Reply {
+            status,
+            content_type: "application/json",
+            body: serde_json::json!({ "error": message })
+                .to_string()
+                .into_bytes(),
+            allow_origin: None,
+        }
+    }
+}
+
+// Api keeps one graph for every client, like the state of the tauri app
+struct Api {
+    graph: Graph,
+    base_path: String,
+    // without an allowed origin, browsers only let pages served from the api read it
+    allow_origin: Option<String>,
+}
+
+pub fn run(
+    activities: &Activities,
+    base_path: &str,
+    addr: &str,
+    allow_origin: Option<String>,
+) -> Result<()> {
+    let server = Server::http(addr).map_err(|err| eyre!("can not listen on {}: {}", addr, err))?;
+    let mut api = Api {
+        graph: Graph::new(activities),
+        base_path: base_path.to_string(),
+        allow_origin,
+    };
+    eprintln!("serving on http://{}/api", addr);
+
+    for mut request in server.incoming_requests() {
+        let find_header = |name: &'static str| {
+            request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv(name))
+                .map(|header| header.value.to_string())
+        };
+        let origin = find_header("Origin");
+        let content_type = find_header("Content-Type");
+
+        let mut body = String::new();
+        let reply = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => api.reply(
+                request.method(),
+                request.url(),
+                origin.as_deref(),
+                content_type.as_deref(),
+                &body,
+            ),
+            Err(err) => Reply::error(400, &err.to_string()),
+        };
+
+        let mut response = Response::from_data(reply.body)
+            .with_status_code(reply.status)
+            .with_header(header("Content-Type", reply.content_type));
+        if let Some(origin) = reply.allow_origin {
+            response = response
+                .with_header(header("Access-Control-Allow-Origin", &origin))
+                .with_header(header("Access-Control-Allow-Headers", "Content-Type"))
+                .with_header(header("Vary", "Origin"));
+        }
+        if let Err(err) = request.respond(response) {
+            eprintln!("can not respond: {}", err);
+        }
+    }
+
+    Ok(())
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).unwrap()
+}
+
+// is_json tells if a content type is application/json, parameters like the charset aside
+fn is_json(content_type: Option<&str>) -> bool {
+    content_type
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"))
+        == Some(true)
+}
+
+// check_filter refuses the filters the graph can not be built from
+fn check_filter(filter: &Filter) -> Result<()> {
+    if filter.min_date.year() < MIN_FILTER_YEAR || filter.max_date.year() > MAX_FILTER_YEAR {
+        return Err(eyre!(
+            "the dates of the filter must be between the years {} and {}",
+            MIN_FILTER_YEAR,
+            MAX_FILTER_YEAR
+        ));
+    }
+    if filter.min_date > filter.max_date {
+        return Err(eyre!(
+            "the filter starts on {} after it ends on {}",
+            filter.min_date,
+            filter.max_date
+        ));
+    }
+    if (filter.max_date - filter.min_date).num_days() >= MAX_FILTER_DAYS {
+        return Err(eyre!("the filter spans more than {} days", MAX_FILTER_DAYS));
+    }
+
+    Ok(())
+}
+
+impl Api {
+    // posts must be json, a form posted from another website can not run the commands
+    fn reply(
+        &mut self,
+        method: &Method,
+        url: &str,
+        origin: Option<&str>,
+        content_type: Option<&str>,
+        body: &str,
+    ) -> Reply {
+        let path = url.split('?').next().unwrap_or(url);
+        let allow_origin = origin
+            .filter(|origin| self.allow_origin.as_deref() == Some(*origin))
+            .map(|origin| origin.to_string());
+
+        let reply = match (method, path.strip_prefix("/api/")) {
+            // preflight of the json posts made from the allowed origin
+            (Method::Options, Some(_)) if allow_origin.is_some() => Ok(Reply {
+                status: 204,
+                content_type: "text/plain",
+                body: Vec::new(),
+                allow_origin: None,
+            }),
+            (Method::Post, Some(_)) if !is_json(content_type) => {
+                Ok(Reply::error(415, "commands are posted as application/json"))
+            }
+            (Method::Post, Some(command)) => self.command(command, body),
+            (Method::Get, Some(path)) if path.starts_with("export/") => {
+                self.export(path.trim_start_matches("export/"))
+            }
+            _ => Ok(Reply::error(
+                404,
+                &format!("no route for {} {}", method, path),
+            )),
+        };
+
+        Reply {
+            allow_origin,
+            ..reply.unwrap_or_else(|err| Reply::error(400, &err.to_string()))
+        }
+    }
+
+    fn command(&mut self, command: &str, body: &str) -> Result<Reply> {
+        // the commands without arguments accept an empty body
+        let body = if body.trim().is_empty() { "{}" } else { body };
+
+        match command {
+            "get_graph" => Reply::json(&(
+                &self.graph.filtered_per_scale_x_segments,
+                &self.graph.filtered_per_scale_y_activities,
+            )),
+            "get_filter" => Reply::json(&(
+                &self.graph.all_per_scale_x_segments,
+                &self.graph.all_filter,
+                &self.graph.applied_filter,
+            )),
+            "apply_filter" => {
+                let args: ApplyFilterArgs = serde_json::from_str(body)?;
+                check_filter(&args.filter)?;
+                self.graph.apply_filter(&args.filter);
+                Reply::json(&())
+            }
+            // query_graph leaves the shared filter alone, each client can keep its own
+            "query_graph" => {
+                let args: ApplyFilterArgs = serde_json::from_str(body)?;
+                check_filter(&args.filter)?;
+                Reply::json(&self.graph.query(&args.filter))
+            }
+            "get_vega_spec" => {
+                let args: VegaSpecArgs = serde_json::from_str(body)?;
+                Reply::json(&self.graph.vega_lite(&args.spec, &args.scale))
+            }
+            "get_running" => {
+                let running = timer::running(&self.base_path)?.map(|running| {
+                    let elapsed = running.elapsed_minutes(&chrono::Local::now().naive_local());
+                    (running, elapsed)
+                });
+                Reply::json(&running)
+            }
+            "get_activities" => {
+                let args: QueryArgs = serde_json::from_str(body)?;
+                let filter = args.filter.as_ref().unwrap_or(&self.graph.applied_filter);
+                Reply::json(
+                    &filter
+                        .apply(&self.graph.all_activities)
+                        .0
+                        .iter()
+                        .map(ActivityRecord::from)
+                        .collect::<Vec<ActivityRecord>>(),
+                )
+            }
+            _ => Ok(Reply::error(404, &format!("unknown command {}", command))),
+        }
+    }
+
+    // export writes the activities of the applied filter in the format of the path
+    fn export(&self, format: &str) -> Result<Reply> {
+        let filtered = self.filtered();
+        let mut body = Vec::new();
+
+        let content_type = match format {
+            "csv" => {
+                export_csv(
+                    &mut body,
+                    &filtered,
+                    &Column::iterator().collect::<Vec<_>>(),
+                )?;
+                "text/csv"
+            }
+            "ndjson" => {
+                json::export_ndjson(&mut body, &filtered)?;
+                "application/x-ndjson"
+            }
+            "ics" => {
+                ical::export_ics(&mut body, &filtered)?;
+                "text/calendar"
+            }
+            #[cfg(feature = "arrow")]
+            "arrow" => {
+                arrow::export_arrow(&mut body, &filtered)?;
+                "application/vnd.apache.arrow.file"
+            }
+            #[cfg(feature = "arrow")]
+            "parquet" => {
+                arrow::export_parquet(&mut body, &filtered)?;
+                "application/vnd.apache.parquet"
+            }
+            _ => return Ok(Reply::error(404, &format!("unknown format {}", format))),
+        };
+
+        Ok(Reply {
+            status: 200,
+            content_type,
+            body,
+            allow_origin: None,
+        })
+    }
+
+    fn filtered(&self) -> Activities {
+        self.graph.applied_filter.apply(&self.graph.all_activities)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use timespent::activity::Action;
+    use timespent::parser::parse_activity;
+
+    fn post(api: &mut Api, command: &str, body: &str) -> (u16, Value) {
+        let reply = api.reply(
+            &Method::Post,
+            &format!("/api/{}", command),
+            None,
+            Some("application/json"),
+            body,
+        );
+
+        (reply.status, serde_json::from_slice(&reply.body).unwrap())
+    }
+
+    #[test]
+    fn test_api() {
+        let mut api = Api {
+            graph: Graph::new(&Activities(vec![
+                parse_activity("2022.05.23", "09h00-10h00: [code][rust] description")
+                    .unwrap()
+                    .1,
+                parse_activity("2022.05.24", "09h00-10h00: [meeting][planning] description")
+                    .unwrap()
+                    .1,
+            ])),
+            base_path: std::env::temp_dir()
+                .join("timespent-serve-missing")
+                .to_string_lossy()
+                .to_string(),
+            allow_origin: None,
+        };
+
+        let (status, filters) = post(&mut api, "get_filter", "");
+        assert_eq!(status, 200);
+        let mut filter: Filter = serde_json::from_value(filters[2].clone()).unwrap();
+        assert_eq!(filter, api.graph.all_filter);
+
+        filter.actions = [Action::Code].into();
+        let (status, _) = post(
+            &mut api,
+            "apply_filter",
+            &json!({ "filter": filter }).to_string(),
+        );
+        assert_eq!(status, 200);
+        assert_eq!(api.graph.applied_filter, filter);
+
+        let (_, activities) = post(&mut api, "get_activities", "{}");
+        assert_eq!(activities.as_array().unwrap().len(), 1);
+        assert_eq!(activities[0]["action"], "code");
+
+        let (_, graph) = post(&mut api, "get_graph", "");
+        assert_eq!(graph[1]["scale_total_minutes"]["All"], json!([60]));
+
+        // a query with another filter does not change the applied one
+        let all = json!({ "filter": api.graph.all_filter }).to_string();
+        let (_, queried) = post(&mut api, "query_graph", &all);
+        assert_eq!(
+            queried["per_scale_y_activities"]["scale_total_minutes"]["All"],
+            json!([120])
+        );
+        assert_eq!(
+            post(&mut api, "get_activities", &all)
+                .1
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(api.graph.applied_filter, filter);
+
+        let (_, spec) = post(
+            &mut api,
+            "get_vega_spec",
+            r#"{"spec": "Totals", "scale": "Day"}"#,
+        );
+        assert_eq!(spec["mark"]["type"], "bar");
+
+        assert_eq!(post(&mut api, "get_running", "").1, Value::Null);
+        assert_eq!(post(&mut api, "apply_filter", "{}").0, 400);
+
+        // filters the graph can not be built from are refused
+        let mut reversed = filter.clone();
+        reversed.min_date = filter.max_date + chrono::Duration::days(1);
+        let mut huge = filter.clone();
+        huge.min_date = chrono::NaiveDate::from_ymd_opt(1900, 1, 1).unwrap();
+        let mut last = filter.clone();
+        last.max_date = chrono::NaiveDate::MAX;
+        for wrong in [reversed, huge, last] {
+            let body = json!({ "filter": wrong }).to_string();
+            assert_eq!(post(&mut api, "apply_filter", &body).0, 400);
+            assert_eq!(post(&mut api, "query_graph", &body).0, 400);
+        }
+        assert_eq!(api.graph.applied_filter, filter);
+
+        // commands are only run from json posts
+        let form = api.reply(
+            &Method::Post,
+            "/api/apply_filter",
+            None,
+            Some("application/x-www-form-urlencoded"),
+            &json!({ "filter": api.graph.all_filter }).to_string(),
+        );
+        assert_eq!(form.status, 415);
+        assert_eq!(
+            api.reply(&Method::Post, "/api/get_graph", None, None, "")
+                .status,
+            415
+        );
+        assert_eq!(api.graph.applied_filter, filter);
+        assert!(is_json(Some("application/json; charset=utf-8")));
+        assert_eq!(post(&mut api, "delete_all", "").0, 404);
+
+        let reply = api.reply(&Method::Get, "/api/export/csv", None, None, "");
+        assert_eq!(reply.content_type, "text/csv");
+        assert_eq!(String::from_utf8(reply.body).unwrap().lines().count(), 2);
+        assert_eq!(
+            api.reply(&Method::Get, "/api/export/pdf", None, None, "")
+                .status,
+            404
+        );
+        assert_eq!(api.reply(&Method::Get, "/", None, None, "").status, 404);
+    }
+
+    #[test]
+    fn test_cors() {
+        let mut api = Api {
+            graph: Graph::new(&Activities(vec![
+                parse_activity("2022.05.23", "09h00-10h00: [code][rust] description")
+                    .unwrap()
+                    .1,
+            ])),
+            base_path: String::new(),
+            allow_origin: None,
+        };
+
+        // cors is off unless an origin is allowed
+        let reply = api.reply(
+            &Method::Options,
+            "/api/get_graph",
+            Some("http://localhost:1420"),
+            None,
+            "",
+        );
+        assert_eq!((reply.status, reply.allow_origin), (404, None));
+        let reply = api.reply(
+            &Method::Get,
+            "/api/export/csv",
+            Some("http://localhost:1420"),
+            None,
+            "",
+        );
+        assert_eq!(reply.allow_origin, None);
+
+        api.allow_origin = Some("http://localhost:1420".to_string());
+        let reply = api.reply(
+            &Method::Options,
+            "/api/get_graph",
+            Some("http://localhost:1420"),
+            None,
+            "",
+        );
+        assert_eq!(reply.status, 204);
+        assert_eq!(
+            reply.allow_origin,
+            Some("http://localhost:1420".to_string())
+        );
+        let reply = api.reply(
+            &Method::Post,
+            "/api/get_graph",
+            Some("http://localhost:1420"),
+            Some("application/json"),
+            "",
+        );
+        assert_eq!(
+            reply.allow_origin,
+            Some("http://localhost:1420".to_string())
+        );
+
+        // other websites get no cors header
+        let reply = api.reply(
+            &Method::Post,
+            "/api/get_activities",
+            Some("https://example.com"),
+            Some("application/json"),
+            "",
+        );
+        assert_eq!(reply.allow_origin, None);
+        let reply = api.reply(
+            &Method::Options,
+            "/api/apply_filter",
+            Some("https://example.com"),
+            None,
+            "",
+        );
+        assert_eq!((reply.status, reply.allow_origin), (404, None));
+    }
+}
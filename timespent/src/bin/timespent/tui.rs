@@ -0,0 +1,385 @@
+use crate::{parse_action, parse_date};
+use eyre::{eyre, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Bar, BarChart, BarGroup, Block, Borders, List, ListItem, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashSet;
+use timespent::activity::{Action, Activities};
+use timespent::export::report::duration;
+use timespent::graph::scale::{naive_date_to_scale_x, Scale};
+use timespent::graph::ui::Graph;
+
+const HELP: &str =
+    "q quit  tab scale  ←/→ series  ↑/↓ pgup/pgdn scroll  / search  : filter  r reset";
+const COMMANDS: &str = "from <date>  to <date>  action <a,b>  project <p,q>  search <text>  reset";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Series {
+    Total,
+    Action(Action),
+    Project(String),
+}
+
+// Dashboard holds the graph and what is shown of it, the filter is only
+// changed through Graph::apply_filter
+struct Dashboard {
+    graph: Graph,
+    activities: Activities,
+    scale: Scale,
+    series: usize,
+    scroll: usize,
+    prompt: Option<String>,
+    message: Option<String>,
+}
+
+pub fn run(activities: &Activities) -> Result<()> {
+    let mut dashboard = Dashboard::new(activities);
+
+    let mut terminal = ratatui::init();
+    let result = dashboard.event_loop(&mut terminal);
+    ratatui::restore();
+
+    result
+}
+
+impl Dashboard {
+    fn new(activities: &Activities) -> Dashboard {
+        let graph = Graph::new(activities);
+
+        Dashboard {
+            activities: graph.applied_filter.apply(&graph.all_activities),
+            graph,
+            scale: Scale::Week,
+            series: 0,
+            scroll: 0,
+            prompt: None,
+            message: None,
+        }
+    }
+
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+
+            if let Some(prompt) = self.prompt.as_mut() {
+                match key.code {
+                    KeyCode::Esc => self.prompt = None,
+                    KeyCode::Enter => {
+                        let command = prompt.clone();
+                        self.prompt = None;
+                        self.message = self.apply_command(&command).err().map(|e| e.to_string());
+                    }
+                    KeyCode::Backspace => {
+                        prompt.pop();
+                    }
+                    KeyCode::Char(c) => prompt.push(c),
+                    _ => {}
+                }
+                continue;
+            }
+
+            self.message = None;
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Tab => self.scale = next_scale(&self.scale),
+                KeyCode::Right => self.series = (self.series + 1) % self.series_list().len(),
+                KeyCode::Left => {
+                    let len = self.series_list().len();
+                    self.series = (self.series + len - 1) % len;
+                }
+                KeyCode::Down => self.scroll_by(1),
+                KeyCode::Up => self.scroll_by(-1),
+                KeyCode::PageDown => self.scroll_by(10),
+                KeyCode::PageUp => self.scroll_by(-10),
+                KeyCode::Char('/') => self.prompt = Some("search ".to_string()),
+                KeyCode::Char(':') => self.prompt = Some(String::new()),
+                KeyCode::Char('r') => self.apply_command("reset")?,
+                _ => {}
+            }
+        }
+    }
+
+    // apply_command reads a filter command of the prompt, an empty value selects everything
+    fn apply_command(&mut self, command: &str) -> Result<()> {
+        let (name, value) = command
+            .trim()
+            .split_once(' ')
+            .unwrap_or((command.trim(), ""));
+        let value = value.trim();
+        let all = &self.graph.all_filter;
+        let mut filter = self.graph.applied_filter.clone();
+
+        match name {
+            "from" => filter.min_date = parse_date(value).map_err(|err| eyre!(err))?,
+            "to" => filter.max_date = parse_date(value).map_err(|err| eyre!(err))?,
+            "action" if value.is_empty() => filter.actions = all.actions.clone(),
+            "action" => {
+                filter.actions = split(value)
+                    .map(parse_action)
+                    .collect::<Result<HashSet<Action>>>()?
+            }
+            "project" if value.is_empty() => filter.projects = all.projects.clone(),
+            "project" => filter.projects = split(value).map(|p| p.to_string()).collect(),
+            "search" => filter.description = (!value.is_empty()).then(|| value.to_string()),
+            "reset" => filter = all.clone(),
+            _ => return Err(eyre!("unknown command {}, use {}", name, COMMANDS)),
+        }
+
+        if filter.max_date < filter.min_date {
+            return Err(eyre!("{} is after {}", filter.min_date, filter.max_date));
+        }
+
+        self.graph.apply_filter(&filter);
+        self.activities = filter.apply(&self.graph.all_activities);
+        self.series = self.series.min(self.series_list().len() - 1);
+        self.scroll = 0;
+
+        Ok(())
+    }
+
+    // series_list is the total followed by the actions and projects left by the filter
+    fn series_list(&self) -> Vec<Series> {
+        let agg = &self.graph.filtered_activities_aggregate;
+        let mut actions = agg.2.iter().copied().collect::<Vec<Action>>();
+        actions.sort_by_key(|action| action.to_string());
+        let mut projects = agg.3.iter().cloned().collect::<Vec<String>>();
+        projects.sort();
+
+        [Series::Total]
+            .into_iter()
+            .chain(actions.into_iter().map(Series::Action))
+            .chain(projects.into_iter().map(Series::Project))
+            .collect()
+    }
+
+    fn series_minutes(&self, series: &Series) -> Vec<i64> {
+        let y_activities = &self.graph.filtered_per_scale_y_activities;
+
+        match series {
+            Series::Total => y_activities.scale_total_minutes.get(&self.scale).cloned(),
+            Series::Action(action) => y_activities.scale_actions_total_minutes[&self.scale]
+                .get(action)
+                .cloned(),
+            Series::Project(project) => y_activities.scale_projects_total_minutes[&self.scale]
+                .get(project)
+                .cloned(),
+        }
+        .unwrap_or_default()
+    }
+
+    fn scroll_by(&mut self, lines: isize) {
+        let max = self.activities.0.len().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(lines).min(max);
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(4),
+                Constraint::Percentage(50),
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ])
+            .split(frame.area());
+
+        self.draw_filter(frame, areas[0]);
+        self.draw_chart(frame, areas[1]);
+        self.draw_activities(frame, areas[2]);
+
+        let footer = match (&self.prompt, &self.message) {
+            (Some(prompt), _) => Paragraph::new(format!(":{}", prompt)),
+            (None, Some(message)) => {
+                Paragraph::new(message.as_str()).style(Style::default().fg(Color::Red))
+            }
+            (None, None) => Paragraph::new(HELP).style(Style::default().fg(Color::DarkGray)),
+        };
+        frame.render_widget(footer, areas[3]);
+    }
+
+    fn draw_filter(&self, frame: &mut Frame, area: Rect) {
+        let filter = &self.graph.applied_filter;
+        let all = &self.graph.all_filter;
+
+        let actions = if filter.actions == all.actions {
+            "all".to_string()
+        } else {
+            sorted(filter.actions.iter().map(|action| action.to_string()))
+        };
+        let projects = if filter.projects == all.projects {
+            "all".to_string()
+        } else {
+            sorted(filter.projects.iter().cloned())
+        };
+        let total = self
+            .graph
+            .filtered_per_scale_y_activities
+            .scale_total_minutes
+            .get(&Scale::All)
+            .map(|minutes| minutes.iter().sum())
+            .unwrap_or(0);
+
+        let lines = vec![
+            Line::from(format!(
+                "{} - {}  {} in {} activities  search: {}",
+                filter.min_date,
+                filter.max_date,
+                duration(total),
+                self.activities.0.len(),
+                filter.description.as_deref().unwrap_or("-"),
+            )),
+            Line::from(format!("actions: {}  projects: {}", actions, projects)),
+        ];
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("filter")),
+            area,
+        );
+    }
+
+    fn draw_chart(&self, frame: &mut Frame, area: Rect) {
+        let series = &self.series_list()[self.series];
+        let title = match series {
+            Series::Total => "total".to_string(),
+            Series::Action(action) => format!("action {}", action),
+            Series::Project(project) => format!("project {}", project),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!("{:?} - {}", self.scale, title));
+
+        let segments = &self.graph.filtered_per_scale_x_segments.values[&self.scale];
+        let labels = segments
+            .iter()
+            .map(|segment| naive_date_to_scale_x(&segment.start_datetime.date(), &self.scale))
+            .collect::<Vec<String>>();
+        let bar_width = labels.iter().map(|label| label.len()).max().unwrap_or(1) as u16;
+
+        // the most recent segments are kept when they do not all fit
+        let fitting = (block.inner(area).width / (bar_width + 1)).max(1) as usize;
+        let bars = labels
+            .into_iter()
+            .zip(self.series_minutes(series))
+            .map(|(label, minutes)| {
+                Bar::default()
+                    .value(minutes.max(0) as u64)
+                    .text_value(duration(minutes))
+                    .label(Line::from(label))
+            })
+            .collect::<Vec<Bar>>();
+        let bars = &bars[bars.len().saturating_sub(fitting)..];
+
+        frame.render_widget(
+            BarChart::default()
+                .block(block)
+                .bar_width(bar_width)
+                .bar_gap(1)
+                .bar_style(Style::default().fg(Color::Cyan))
+                .value_style(Style::default().fg(Color::Black).bg(Color::Cyan))
+                .data(BarGroup::default().bars(bars)),
+            area,
+        );
+    }
+
+    fn draw_activities(&self, frame: &mut Frame, area: Rect) {
+        let items = self
+            .activities
+            .0
+            .iter()
+            .skip(self.scroll)
+            .map(|activity| {
+                ListItem::new(format!(
+                    "{} {} {}",
+                    activity.start_datetime.date(),
+                    activity,
+                    activity.source
+                ))
+            })
+            .collect::<Vec<ListItem>>();
+
+        frame.render_widget(
+            List::new(items).block(Block::default().borders(Borders::ALL).title(format!(
+                "activities {}/{}",
+                (self.scroll + 1).min(self.activities.0.len()),
+                self.activities.0.len()
+            ))),
+            area,
+        );
+    }
+}
+
+fn next_scale(scale: &Scale) -> Scale {
+    let scales = Scale::iterator().collect::<Vec<Scale>>();
+    let idx = scales.iter().position(|s| s == scale).unwrap_or(0);
+
+    scales[(idx + 1) % scales.len()]
+}
+
+fn split(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(|v| v.trim()).filter(|v| !v.is_empty())
+}
+
+fn sorted(values: impl Iterator<Item = String>) -> String {
+    let mut values = values.collect::<Vec<String>>();
+    values.sort();
+
+    values.join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use timespent::parser::parse_activity;
+
+    #[test]
+    fn test_apply_command() {
+        let mut dashboard = Dashboard::new(&Activities(vec![
+            parse_activity("2022.05.23", "09h00-10h00: [code][rust] loader")
+                .unwrap()
+                .1,
+            parse_activity("2022.05.24", "09h00-10h00: [meeting][planning] weekly")
+                .unwrap()
+                .1,
+            parse_activity("2022.05.25", "09h00-10h00: [code][rust] parser")
+                .unwrap()
+                .1,
+        ]));
+        assert_eq!(dashboard.series_list().len(), 5);
+
+        dashboard.apply_command("action code").unwrap();
+        assert_eq!(dashboard.activities.0.len(), 2);
+        assert_eq!(
+            dashboard.series_list(),
+            vec![
+                Series::Total,
+                Series::Action(Action::Code),
+                Series::Project("rust".to_string())
+            ]
+        );
+
+        dashboard.apply_command("from 2022-05-24").unwrap();
+        dashboard.apply_command("search pars").unwrap();
+        dashboard.scale = Scale::Day;
+        assert_eq!(dashboard.activities.0.len(), 1);
+        assert_eq!(dashboard.series_minutes(&Series::Total), vec![0, 60]);
+
+        assert!(dashboard.apply_command("to 2022-05-20").is_err());
+        assert!(dashboard.apply_command("action nothing").is_err());
+        assert!(dashboard.apply_command("bogus").is_err());
+        assert_eq!(
+            dashboard.graph.applied_filter.description,
+            Some("pars".to_string())
+        );
+
+        dashboard.apply_command("reset").unwrap();
+        assert_eq!(dashboard.activities.0.len(), 3);
+        assert_eq!(dashboard.graph.applied_filter, dashboard.graph.all_filter);
+    }
+}
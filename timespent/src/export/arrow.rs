@@ -0,0 +1,223 @@
+use crate::activity::Activities;
+use ::arrow::array::{
+    ArrayRef, Int64Array, ListBuilder, StringArray, StringBuilder, TimestampSecondArray,
+};
+use ::arrow::ipc::writer::FileWriter;
+use ::arrow::record_batch::RecordBatch;
+use eyre::Result;
+use parquet::arrow::ArrowWriter;
+use std::io::Write;
+use std::sync::Arc;
+
+// export_arrow writes the activities as an arrow ipc file
+pub fn export_arrow<W: Write>(writer: W, activities: &Activities) -> Result<()> {
+    let batch = record_batch(activities)?;
+
+    let mut writer = FileWriter::try_new(writer, &batch.schema())?;
+    writer.write(&batch)?;
+    writer.finish()?;
+
+    Ok(())
+}
+
+pub fn export_parquet<W: Write + Send>(writer: W, activities: &Activities) -> Result<()> {
+    let batch = record_batch(activities)?;
+
+    let mut writer = ArrowWriter::try_new(writer, batch.schema(), None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+
+    Ok(())
+}
+
+// record_batch has one row per activity, start and end are timestamps without
+// timezone as day files hold local times, projects are a sorted list
+fn record_batch(activities: &Activities) -> Result<RecordBatch> {
+    let activities = &activities.0;
+
+    let mut projects = ListBuilder::new(StringBuilder::new());
+    for activity in activities {
+        let mut activity_projects = activity.projects.iter().collect::<Vec<&String>>();
+        activity_projects.sort();
+        for project in activity_projects {
+            projects.values().append_value(project);
+        }
+        projects.append(true);
+    }
+
+    let batch = RecordBatch::try_from_iter_with_nullable(vec![
+        (
+            "id",
+            Arc::new(StringArray::from_iter(
+                activities
+                    .iter()
+                    .map(|activity| activity.id.as_ref().map(|id| id.to_string())),
+            )) as ArrayRef,
+            true,
+        ),
+        (
+            "start",
+            Arc::new(TimestampSecondArray::from_iter_values(
+                activities
+                    .iter()
+                    .map(|activity| activity.start_datetime.and_utc().timestamp()),
+            )),
+            false,
+        ),
+        (
+            "end",
+            Arc::new(TimestampSecondArray::from_iter_values(
+                activities
+                    .iter()
+                    .map(|activity| activity.end_datetime.and_utc().timestamp()),
+            )),
+            false,
+        ),
+        (
+            "duration_minutes",
+            Arc::new(Int64Array::from_iter_values(activities.iter().map(
+                |activity| (activity.end_datetime - activity.start_datetime).num_minutes(),
+            ))),
+            false,
+        ),
+        (
+            "action",
+            Arc::new(StringArray::from_iter_values(
+                activities
+                    .iter()
+                    .map(|activity| activity.action.to_string()),
+            )),
+            false,
+        ),
+        ("projects", Arc::new(projects.finish()), false),
+        (
+            "description",
+            Arc::new(StringArray::from_iter_values(
+                activities.iter().map(|activity| &activity.description),
+            )),
+            false,
+        ),
+        (
+            "source",
+            Arc::new(StringArray::from_iter_values(
+                activities.iter().map(|activity| &activity.source),
+            )),
+            false,
+        ),
+    ])?;
+
+    Ok(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::{Action, Activity};
+    use crate::test_utils::test_dir;
+    use ::arrow::array::{Array, AsArray};
+    use ::arrow::datatypes::{DataType, TimeUnit};
+    use ::arrow::ipc::reader::FileReader;
+    use chrono::NaiveDate;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::fs::File;
+    use std::io::Cursor;
+    use std::path::Path;
+
+    fn activities() -> Activities {
+        Activities(vec![
+            Activity {
+                start_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                    .unwrap()
+                    .and_hms_opt(9, 0, 0)
+                    .unwrap(),
+                end_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                    .unwrap()
+                    .and_hms_opt(10, 30, 0)
+                    .unwrap(),
+                description: "loader".to_string(),
+                action: Action::Code,
+                projects: ["rust".to_string(), "perf".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
+            },
+            Activity {
+                start_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                    .unwrap()
+                    .and_hms_opt(14, 0, 0)
+                    .unwrap(),
+                end_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                    .unwrap()
+                    .and_hms_opt(15, 0, 0)
+                    .unwrap(),
+                description: "weekly".to_string(),
+                action: Action::Meeting,
+                projects: [].into(),
+                source: "team".to_string(),
+                id: None,
+            },
+        ])
+    }
+
+    fn check_batch(batch: &RecordBatch) {
+        assert_eq!(batch.num_rows(), 2);
+        assert_eq!(
+            batch.schema().field_with_name("start").unwrap().data_type(),
+            &DataType::Timestamp(TimeUnit::Second, None)
+        );
+
+        let start = batch
+            .column_by_name("start")
+            .unwrap()
+            .as_primitive::<::arrow::datatypes::TimestampSecondType>();
+        assert_eq!(
+            start.value(0),
+            NaiveDate::from_ymd_opt(2022, 5, 27)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap()
+                .and_utc()
+                .timestamp()
+        );
+
+        let duration = batch
+            .column_by_name("duration_minutes")
+            .unwrap()
+            .as_primitive::<::arrow::datatypes::Int64Type>();
+        assert_eq!(duration.values().to_vec(), vec![90, 60]);
+
+        let projects = batch.column_by_name("projects").unwrap().as_list::<i32>();
+        let first = projects.value(0);
+        let first = first.as_string::<i32>();
+        assert_eq!(
+            first.iter().flatten().collect::<Vec<&str>>(),
+            vec!["perf", "rust"]
+        );
+        assert_eq!(projects.value(1).len(), 0);
+
+        let action = batch.column_by_name("action").unwrap().as_string::<i32>();
+        assert_eq!(action.value(1), "meeting");
+        assert!(batch.column_by_name("id").unwrap().is_null(0));
+    }
+
+    #[test]
+    fn test_export_arrow() {
+        let mut out = Vec::new();
+        export_arrow(&mut out, &activities()).unwrap();
+
+        let mut reader = FileReader::try_new(Cursor::new(out), None).unwrap();
+        check_batch(&reader.next().unwrap().unwrap());
+    }
+
+    #[test]
+    fn test_export_parquet() {
+        let dir = test_dir("export-parquet");
+        let path = Path::new(dir.path()).join("activities.parquet");
+        export_parquet(File::create(&path).unwrap(), &activities()).unwrap();
+
+        let mut reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        check_batch(&reader.next().unwrap().unwrap());
+    }
+}
@@ -0,0 +1,129 @@
+use super::ActivityRecord;
+use crate::activity::Activities;
+use ::csv::Writer;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    Id,
+    Date,
+    Start,
+    End,
+    DurationMinutes,
+    Action,
+    Projects,
+    Description,
+    Source,
+}
+
+impl Column {
+    pub fn iterator() -> impl Iterator<Item = Column> {
+        [
+            Column::Id,
+            Column::Date,
+            Column::Start,
+            Column::End,
+            Column::DurationMinutes,
+            Column::Action,
+            Column::Projects,
+            Column::Description,
+            Column::Source,
+        ]
+        .iter()
+        .copied()
+    }
+
+    fn header(&self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::Date => "date",
+            Column::Start => "start",
+            Column::End => "end",
+            Column::DurationMinutes => "duration_minutes",
+            Column::Action => "action",
+            Column::Projects => "projects",
+            Column::Description => "description",
+            Column::Source => "source",
+        }
+    }
+
+    fn value(&self, record: &ActivityRecord) -> String {
+        match self {
+            Column::Id => record.id.clone().unwrap_or_default(),
+            Column::Date => record.start[..10].to_string(),
+            Column::Start => record.start.clone(),
+            Column::End => record.end.clone(),
+            Column::DurationMinutes => record.duration_minutes.to_string(),
+            Column::Action => record.action.clone(),
+            Column::Projects => record.projects.join(";"),
+            Column::Description => record.description.clone(),
+            Column::Source => record.source.clone(),
+        }
+    }
+}
+
+// export_csv writes one row per activity with the given columns, projects are separated by ;
+pub fn export_csv<W: Write>(writer: W, activities: &Activities, columns: &[Column]) -> Result<()> {
+    let mut writer = Writer::from_writer(writer);
+
+    writer.write_record(columns.iter().map(|column| column.header()))?;
+    for activity in &activities.0 {
+        let record = ActivityRecord::from(activity);
+        writer.write_record(columns.iter().map(|column| column.value(&record)))?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::{Action, Activity};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_export_csv() {
+        let activities = Activities(vec![Activity {
+            start_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap(),
+            end_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                .unwrap()
+                .and_hms_opt(10, 30, 0)
+                .unwrap(),
+            description: "headers, yamls".to_string(),
+            action: Action::Review,
+            projects: ["rust".to_string(), "rest-apis".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
+        }]);
+
+        let mut out = Vec::new();
+        export_csv(
+            &mut out,
+            &activities,
+            &[
+                Column::Date,
+                Column::Start,
+                Column::End,
+                Column::DurationMinutes,
+                Column::Action,
+                Column::Projects,
+                Column::Description,
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "date,start,end,duration_minutes,action,projects,description
+2022-05-27,2022-05-27T09:00:00,2022-05-27T10:30:00,90,review,rest-apis;rust,\"headers, yamls\"
+"
+        );
+    }
+}
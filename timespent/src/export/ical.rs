@@ -0,0 +1,224 @@
+use crate::activity::{content_hash, Action, Activities};
+use chrono::{NaiveDateTime, Utc};
+use eyre::Result;
+use std::collections::HashMap;
+use std::io::Write;
+
+const ICS_DATETIME: &str = "%Y%m%dT%H%M%S";
+
+// export_ics writes each activity as a calendar event, times are floating local times
+pub fn export_ics<W: Write>(writer: W, activities: &Activities) -> Result<()> {
+    write_calendar(writer, activities, &Utc::now().naive_utc())
+}
+
+// the uid is made of the hash of the source, the day and the line of the activity in its
+// day file, so exporting again after editing the times or the description of a line updates
+// the same event. inserting a line above shifts the uids of the following lines, their
+// events are then updated rather than duplicated. activities without a line, like the ones
+// of an import not written yet, fall back to their start time. the sequence is the export
+// time in minutes, it only grows so calendars take each export as the latest revision
+fn write_calendar<W: Write>(
+    mut writer: W,
+    activities: &Activities,
+    dtstamp: &NaiveDateTime,
+) -> Result<()> {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//timespent//timespent//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+
+    // activities of a source starting at the same time get a suffix to keep their uids apart
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let sequence = dtstamp.and_utc().timestamp().div_euclid(60).max(0);
+
+    for activity in &activities.0 {
+        let mut uid = match &activity.id {
+            Some(id) => format!(
+                "{}-{}-{}",
+                id.date.format("%Y%m%d"),
+                id.line,
+                content_hash(&activity.source)
+            ),
+            None => format!(
+                "{}-{}",
+                activity.start_datetime.format("%Y%m%dT%H%M"),
+                content_hash(&activity.source)
+            ),
+        };
+        let count = seen.entry(uid.clone()).or_insert(0);
+        if *count > 0 {
+            uid = format!("{}-{}", uid, count);
+        }
+        *count += 1;
+
+        let mut projects = activity.projects.iter().cloned().collect::<Vec<String>>();
+        projects.sort();
+        let mut categories = Vec::new();
+        if activity.action != Action::Unknown {
+            categories.push(activity.action.to_string());
+        }
+        categories.extend(projects.iter().cloned());
+
+        let summary = if activity.description.is_empty() {
+            projects.join(" ")
+        } else {
+            activity.description.clone()
+        };
+
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}@timespent", uid));
+        lines.push(format!("DTSTAMP:{}Z", dtstamp.format(ICS_DATETIME)));
+        lines.push(format!("SEQUENCE:{}", sequence));
+        lines.push(format!(
+            "DTSTART:{}",
+            activity.start_datetime.format(ICS_DATETIME)
+        ));
+        lines.push(format!(
+            "DTEND:{}",
+            activity.end_datetime.format(ICS_DATETIME)
+        ));
+        lines.push(format!("SUMMARY:{}", escape(&summary)));
+        if !categories.is_empty() {
+            lines.push(format!(
+                "CATEGORIES:{}",
+                categories
+                    .iter()
+                    .map(|category| escape(category))
+                    .collect::<Vec<String>>()
+                    .join(",")
+            ));
+        }
+        if !activity.source.is_empty() {
+            lines.push(format!("X-TIMESPENT-SOURCE:{}", escape(&activity.source)));
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    for line in lines {
+        writer.write_all(fold(&line).as_bytes())?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+fn escape(input: &str) -> String {
+    input
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// lines longer than 75 bytes are folded, continuation lines start with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut len = 0;
+
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::{Activity, ActivityId};
+    use crate::import::ical::read_events;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_write_calendar() {
+        let activity = Activity {
+            start_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                .unwrap()
+                .and_hms_opt(15, 45, 0)
+                .unwrap(),
+            end_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                .unwrap()
+                .and_hms_opt(17, 30, 0)
+                .unwrap(),
+            description: "with MT, about the rest api; and a description long enough to be folded"
+                .to_string(),
+            action: Action::Meeting,
+            projects: ["manager".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
+        };
+        let mut second = activity.clone();
+        second.id = Some(ActivityId::new(
+            "work",
+            NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+            3,
+            &second,
+        ));
+        let activities = Activities(vec![activity.clone(), activity, second]);
+        let dtstamp = NaiveDate::from_ymd_opt(2022, 6, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        let mut out = Vec::new();
+        write_calendar(&mut out, &activities, &dtstamp).unwrap();
+        let ics = String::from_utf8(out).unwrap();
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTART:20220527T154500\r\n"));
+        assert!(ics.contains("CATEGORIES:meeting,manager\r\n"));
+        assert!(ics.contains("SEQUENCE:27567360\r\n"));
+        assert!(ics.lines().all(|line| line.len() <= 75));
+
+        let events = read_events(
+            ics.as_bytes(),
+            &Utc,
+            &NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+            &NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(events.len(), 3);
+        assert_ne!(events[0].uid, events[1].uid);
+        assert_eq!(
+            events[2].uid,
+            format!("20220527-3-{}@timespent", content_hash("work"))
+        );
+        assert_eq!(events[0].categories, vec!["meeting", "manager"]);
+
+        // exporting again gives the same uids
+        let mut out = Vec::new();
+        write_calendar(&mut out, &activities, &dtstamp).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), ics);
+
+        // an edited activity keeps its uid, a line keeps it even when its start moves
+        let mut edited = activities.clone();
+        edited.0[0].description = "with MT".to_string();
+        edited.0[0].end_datetime += chrono::Duration::minutes(30);
+        edited.0[2].start_datetime -= chrono::Duration::minutes(45);
+        let mut out = Vec::new();
+        write_calendar(&mut out, &edited, &dtstamp).unwrap();
+        let edited_events = read_events(
+            out.as_slice(),
+            &Utc,
+            &NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+            &NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            edited_events[0].start_datetime,
+            events[2].start_datetime - chrono::Duration::minutes(45)
+        );
+        assert_eq!(edited_events[0].uid, events[2].uid);
+        assert_eq!(edited_events[1].uid, events[0].uid);
+    }
+}
@@ -0,0 +1,67 @@
+use super::ActivityRecord;
+use crate::activity::Activities;
+use eyre::Result;
+use std::io::Write;
+
+// export_ndjson writes one json object per line and per activity
+pub fn export_ndjson<W: Write>(mut writer: W, activities: &Activities) -> Result<()> {
+    for activity in &activities.0 {
+        serde_json::to_writer(&mut writer, &ActivityRecord::from(activity))?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::{Action, Activity, ActivityId};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_export_ndjson() {
+        let activity = Activity {
+            start_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap(),
+            end_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                .unwrap()
+                .and_hms_opt(9, 30, 0)
+                .unwrap(),
+            description: "news".to_string(),
+            action: Action::Unknown,
+            projects: ["veille".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
+        };
+        let id = ActivityId::new(
+            "work",
+            NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+            1,
+            &activity,
+        );
+        let activities = Activities(vec![
+            Activity {
+                id: Some(id.clone()),
+                ..activity.clone()
+            },
+            activity,
+        ]);
+
+        let mut out = Vec::new();
+        export_ndjson(&mut out, &activities).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "{{\"id\":\"{}\",\"start\":\"2022-05-27T09:00:00\",\"end\":\"2022-05-27T09:30:00\",\"duration_minutes\":30,\"action\":\"unknown\",\"projects\":[\"veille\"],\"description\":\"news\",\"source\":\"work\"}}
+{{\"id\":null,\"start\":\"2022-05-27T09:00:00\",\"end\":\"2022-05-27T09:30:00\",\"duration_minutes\":30,\"action\":\"unknown\",\"projects\":[\"veille\"],\"description\":\"news\",\"source\":\"work\"}}
+",
+                id
+            )
+        );
+    }
+}
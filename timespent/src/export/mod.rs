@@ -0,0 +1,45 @@
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod csv;
+pub mod ical;
+pub mod json;
+pub mod report;
+pub mod standup;
+pub mod timesheet;
+
+use crate::activity::Activity;
+use serde::{Deserialize, Serialize};
+
+const ISO_DATETIME: &str = "%Y-%m-%dT%H:%M:%S";
+
+// ActivityRecord is the flat form of an activity shared by the exporters,
+// times are iso-8601 and projects are sorted
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct ActivityRecord {
+    pub id: Option<String>,
+    pub start: String,
+    pub end: String,
+    pub duration_minutes: i64,
+    pub action: String,
+    pub projects: Vec<String>,
+    pub description: String,
+    pub source: String,
+}
+
+impl From<&Activity> for ActivityRecord {
+    fn from(activity: &Activity) -> Self {
+        let mut projects = activity.projects.iter().cloned().collect::<Vec<String>>();
+        projects.sort();
+
+        ActivityRecord {
+            id: activity.id.as_ref().map(|id| id.to_string()),
+            start: activity.start_datetime.format(ISO_DATETIME).to_string(),
+            end: activity.end_datetime.format(ISO_DATETIME).to_string(),
+            duration_minutes: (activity.end_datetime - activity.start_datetime).num_minutes(),
+            action: activity.action.to_string(),
+            projects,
+            description: activity.description.clone(),
+            source: activity.source.clone(),
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use crate::activity::Activities;
+use crate::graph::scale::Scale;
+use crate::graph::ui::Filter;
+use crate::graph::x_segments::ScaleXSegments;
+use crate::graph::y_activities::YActivities;
+use chrono::Duration;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tinytemplate::{format_unescaped, TinyTemplate};
+
+const TOP_DESCRIPTIONS: usize = 10;
+
+pub const MARKDOWN_TEMPLATE: &str = "# Report {start_date} - {end_date}
+
+Total: **{total.duration}**, {total.difference} compared to {previous_start_date} - {previous_end_date}
+
+## Actions
+
+| action | time | previous | difference |
+|---|---|---|---|
+{{ for line in actions }}| {line.name} | {line.duration} | {line.previous_duration} | {line.difference} |
+{{ endfor }}
+## Projects
+
+| project | time | previous | difference |
+|---|---|---|---|
+{{ for line in projects }}| {line.name} | {line.duration} | {line.previous_duration} | {line.difference} |
+{{ endfor }}
+## Top descriptions
+
+{{ for line in top_descriptions }}- {line.name}: {line.duration}
+{{ endfor }}
+## Days
+
+| day | time | details |
+|---|---|---|
+{{ for day in days }}| {day.date} | {day.duration} | {day.details} |
+{{ endfor }}";
+
+// the html report is self-contained, bars are sized by the share of the period total
+pub const HTML_TEMPLATE: &str = "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Report {start_date} - {end_date}</title>
+<style>
+body \\{ font-family: sans-serif; max-width: 50em; margin: auto; }
+table \\{ border-collapse: collapse; width: 100%; }
+td, th \\{ border-bottom: 1px solid #ddd; padding: 0.2em 0.5em; text-align: left; }
+.bar \\{ background: #4e79a7; height: 0.8em; }
+</style>
+</head>
+<body>
+<h1>Report {start_date} - {end_date}</h1>
+<p>Total: <strong>{total.duration}</strong>, {total.difference} compared to {previous_start_date} - {previous_end_date}</p>
+<h2>Actions</h2>
+<table>
+<tr><th>action</th><th>time</th><th>previous</th><th>difference</th><th></th></tr>
+{{ for line in actions }}<tr><td>{line.name}</td><td>{line.duration}</td><td>{line.previous_duration}</td><td>{line.difference}</td><td><div class=\"bar\" style=\"width: {line.percent}%\"></div></td></tr>
+{{ endfor }}</table>
+<h2>Projects</h2>
+<table>
+<tr><th>project</th><th>time</th><th>previous</th><th>difference</th><th></th></tr>
+{{ for line in projects }}<tr><td>{line.name}</td><td>{line.duration}</td><td>{line.previous_duration}</td><td>{line.difference}</td><td><div class=\"bar\" style=\"width: {line.percent}%\"></div></td></tr>
+{{ endfor }}</table>
+<h2>Top descriptions</h2>
+<ul>
+{{ for line in top_descriptions }}<li>{line.name}: {line.duration}</li>
+{{ endfor }}</ul>
+<h2>Days</h2>
+<table>
+<tr><th>day</th><th>time</th><th>details</th></tr>
+{{ for day in days }}<tr><td>{day.date}</td><td>{day.duration}</td><td>{day.details}</td></tr>
+{{ endfor }}</table>
+</body>
+</html>
+";
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+// durations are written as 12h30 so templates do not need any arithmetic
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct ReportLine {
+    pub name: String,
+    pub minutes: i64,
+    pub previous_minutes: i64,
+    pub duration: String,
+    pub previous_duration: String,
+    pub difference: String,
+    pub percent: i64,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct ReportDay {
+    pub date: String,
+    pub minutes: i64,
+    pub duration: String,
+    // time per action of the day, longest first
+    pub details: String,
+}
+
+// Report is the context given to the templates
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct Report {
+    pub start_date: String,
+    pub end_date: String,
+    pub previous_start_date: String,
+    pub previous_end_date: String,
+    pub total: ReportLine,
+    pub actions: Vec<ReportLine>,
+    pub projects: Vec<ReportLine>,
+    pub top_descriptions: Vec<ReportLine>,
+    pub days: Vec<ReportDay>,
+}
+
+impl Report {
+    // new compares the filtered activities with the previous period of the same length
+    pub fn new(activities: &Activities, filter: &Filter) -> Report {
+        let length = filter.max_date - filter.min_date + Duration::days(1);
+        let previous_filter = Filter {
+            min_date: filter.min_date - length,
+            max_date: filter.max_date - length,
+            ..filter.clone()
+        };
+
+        let filtered_activities = filter.apply(activities);
+        let previous_activities = previous_filter.apply(activities);
+
+        let sxs = ScaleXSegments::new(&filter.min_date, &filter.max_date);
+        let y_activities = y_activities(&filtered_activities, &sxs);
+        let previous_y_activities = y_activities_all(&previous_activities, &previous_filter);
+
+        let total_minutes = y_activities.scale_total_minutes[&Scale::All][0];
+        let line = |name: String, minutes: i64, previous_minutes: i64| {
+            report_line(name, minutes, previous_minutes, total_minutes)
+        };
+
+        let total = line(
+            "total".to_string(),
+            total_minutes,
+            previous_y_activities.scale_total_minutes[&Scale::All][0],
+        );
+
+        let previous_actions = previous_y_activities.scale_actions_total_minutes[&Scale::All]
+            .iter()
+            .map(|(action, minutes)| (action.to_string(), minutes[0]))
+            .collect::<HashMap<String, i64>>();
+        let actions = sorted_lines(
+            y_activities.scale_actions_total_minutes[&Scale::All]
+                .iter()
+                .map(|(action, minutes)| {
+                    let name = action.to_string();
+                    let previous = previous_actions.get(&name).copied().unwrap_or(0);
+                    line(name, minutes[0], previous)
+                })
+                .collect(),
+        );
+
+        let previous_projects = &previous_y_activities.scale_projects_total_minutes[&Scale::All];
+        let projects = sorted_lines(
+            y_activities.scale_projects_total_minutes[&Scale::All]
+                .iter()
+                .map(|(project, minutes)| {
+                    let previous = previous_projects.get(project).map_or(0, |m| m[0]);
+                    line(project.clone(), minutes[0], previous)
+                })
+                .collect(),
+        );
+
+        let mut descriptions: HashMap<&str, i64> = HashMap::new();
+        filtered_activities
+            .0
+            .iter()
+            .filter(|activity| !activity.description.is_empty())
+            .for_each(|activity| {
+                *descriptions.entry(&activity.description).or_insert(0) +=
+                    (activity.end_datetime - activity.start_datetime).num_minutes()
+            });
+        let mut top_descriptions = sorted_lines(
+            descriptions
+                .into_iter()
+                .map(|(description, minutes)| line(description.to_string(), minutes, 0))
+                .collect(),
+        );
+        top_descriptions.truncate(TOP_DESCRIPTIONS);
+
+        let days = sxs.values[&Scale::Day]
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| y_activities.scale_total_minutes[&Scale::Day][*idx] != 0)
+            .map(|(idx, segment)| {
+                let mut actions = y_activities.scale_actions_total_minutes[&Scale::Day]
+                    .iter()
+                    .filter(|(_, minutes)| minutes[idx] != 0)
+                    .map(|(action, minutes)| (minutes[idx], action.to_string()))
+                    .collect::<Vec<(i64, String)>>();
+                actions.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+
+                let minutes = y_activities.scale_total_minutes[&Scale::Day][idx];
+                ReportDay {
+                    date: segment.start_datetime.format("%a %Y.%m.%d").to_string(),
+                    minutes,
+                    duration: duration(minutes),
+                    details: actions
+                        .iter()
+                        .map(|(minutes, action)| format!("{} {}", action, duration(*minutes)))
+                        .collect::<Vec<String>>()
+                        .join(", "),
+                }
+            })
+            .collect();
+
+        Report {
+            start_date: filter.min_date.format("%Y.%m.%d").to_string(),
+            end_date: filter.max_date.format("%Y.%m.%d").to_string(),
+            previous_start_date: previous_filter.min_date.format("%Y.%m.%d").to_string(),
+            previous_end_date: previous_filter.max_date.format("%Y.%m.%d").to_string(),
+            total,
+            actions,
+            projects,
+            top_descriptions,
+            days,
+        }
+    }
+
+    // render uses the given tinytemplate template, or the default one of the format,
+    // values are html escaped in html reports only
+    pub fn render(&self, format: ReportFormat, template: Option<&str>) -> Result<String> {
+        let template = template.unwrap_or(match format {
+            ReportFormat::Markdown => MARKDOWN_TEMPLATE,
+            ReportFormat::Html => HTML_TEMPLATE,
+        });
+
+        let mut tt = TinyTemplate::new();
+        if format == ReportFormat::Markdown {
+            tt.set_default_formatter(&format_unescaped);
+        }
+        tt.add_template("report", template)?;
+
+        Ok(tt.render("report", self)?)
+    }
+}
+
+fn y_activities(activities: &Activities, sxs: &ScaleXSegments) -> YActivities {
+    let agg = activities.aggregate_all();
+    YActivities::new(activities, &agg.2, &agg.3, &agg.4, sxs)
+}
+
+fn y_activities_all(activities: &Activities, filter: &Filter) -> YActivities {
+    y_activities(
+        activities,
+        &ScaleXSegments::new(&filter.min_date, &filter.max_date),
+    )
+}
+
+fn report_line(name: String, minutes: i64, previous_minutes: i64, total: i64) -> ReportLine {
+    let difference = minutes - previous_minutes;
+
+    ReportLine {
+        name,
+        minutes,
+        previous_minutes,
+        duration: duration(minutes),
+        previous_duration: duration(previous_minutes),
+        difference: format!(
+            "{}{}",
+            if difference < 0 { "-" } else { "+" },
+            duration(difference.abs())
+        ),
+        percent: if total == 0 { 0 } else { minutes * 100 / total },
+    }
+}
+
+// lines without time in the period nor in the previous one are dropped, longest first
+fn sorted_lines(mut lines: Vec<ReportLine>) -> Vec<ReportLine> {
+    lines.retain(|line| line.minutes != 0 || line.previous_minutes != 0);
+    lines.sort_by(|a, b| b.minutes.cmp(&a.minutes).then(a.name.cmp(&b.name)));
+
+    lines
+}
+
+// duration writes minutes like 12h30
+pub fn duration(minutes: i64) -> String {
+    format!("{}h{:02}", minutes / 60, minutes % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Action;
+    use crate::test_utils::activity;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_report() {
+        let activities = Activities(vec![
+            activity(16, (9, 0), (10, 0), Action::Code, &["rust"], "loader"),
+            activity(23, (9, 0), (11, 0), Action::Code, &["rust"], "loader"),
+            activity(23, (14, 0), (15, 0), Action::Meeting, &["rust"], "<weekly>"),
+            activity(25, (9, 0), (10, 0), Action::Code, &["rust"], "parser"),
+        ]);
+        let agg = activities.aggregate_all();
+        let filter = Filter {
+            min_date: NaiveDate::from_ymd_opt(2022, 5, 23).unwrap(),
+            max_date: NaiveDate::from_ymd_opt(2022, 5, 29).unwrap(),
+            actions: agg.2,
+            projects: agg.3,
+            sources: agg.4,
+            description: None,
+        };
+
+        let report = Report::new(&activities, &filter);
+
+        assert_eq!(report.previous_start_date, "2022.05.16");
+        assert_eq!(report.total.duration, "4h00");
+        assert_eq!(report.total.difference, "+3h00");
+        assert_eq!(
+            report
+                .actions
+                .iter()
+                .map(|line| (line.name.as_str(), line.minutes, line.previous_minutes))
+                .collect::<Vec<(&str, i64, i64)>>(),
+            vec![("code", 180, 60), ("meeting", 60, 0)]
+        );
+        assert_eq!(report.top_descriptions[0].name, "loader");
+        assert_eq!(report.top_descriptions[0].minutes, 120);
+        assert_eq!(report.days.len(), 2);
+        assert_eq!(report.days[0].details, "code 2h00, meeting 1h00");
+
+        let markdown = report.render(ReportFormat::Markdown, None).unwrap();
+        assert!(markdown.starts_with("# Report 2022.05.23 - 2022.05.29\n"));
+        assert!(markdown.contains("| code | 3h00 | 1h00 | +2h00 |\n"));
+        assert!(markdown.contains("- <weekly>: 1h00\n"));
+
+        let html = report.render(ReportFormat::Html, None).unwrap();
+        assert!(html.contains("<li>&lt;weekly&gt;: 1h00</li>"));
+        assert!(html.contains("body { font-family"));
+
+        let custom = report
+            .render(
+                ReportFormat::Markdown,
+                Some("{{ for line in projects }}{line.name} {line.duration}{{ endfor }}"),
+            )
+            .unwrap();
+        assert_eq!(custom, "rust 4h00");
+    }
+}
@@ -0,0 +1,157 @@
+use super::report::duration;
+use crate::activity::Activities;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+// StandupProject holds the time spent on a project, with the time per description
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct StandupProject {
+    pub name: String,
+    pub minutes: i64,
+    pub descriptions: Vec<(String, i64)>,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct Standup {
+    pub date: NaiveDate,
+    pub projects: Vec<StandupProject>,
+}
+
+impl Standup {
+    // new summarizes the previous working day before today, None when nothing was logged
+    pub fn new(activities: &Activities, today: &NaiveDate) -> Option<Standup> {
+        let agg = activities.aggregate_all();
+        // empty sets keep every activity, the ones without any project are listed as other
+        let day_activities = |date: &NaiveDate| {
+            activities.filter(
+                date,
+                date,
+                &HashSet::new(),
+                &HashSet::new(),
+                &HashSet::new(),
+                &None,
+            )
+        };
+
+        // weekends and days without any activity are skipped
+        let (date, day_activities) = (1..)
+            .map(|days| *today - Duration::days(days))
+            .take_while(|date| *date >= agg.0)
+            .filter(|date| !matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+            .map(|date| (date, day_activities(&date)))
+            .find(|(_, day_activities)| !day_activities.0.is_empty())?;
+
+        // an activity on several projects is grouped under all of them together,
+        // so its time is not counted twice
+        let mut projects: HashMap<String, StandupProject> = HashMap::new();
+        for activity in &day_activities.0 {
+            let mut names = activity.projects.iter().cloned().collect::<Vec<String>>();
+            names.sort();
+            let name = if names.is_empty() {
+                "other".to_string()
+            } else {
+                names.join(", ")
+            };
+            let minutes = (activity.end_datetime - activity.start_datetime).num_minutes();
+
+            let project = projects.entry(name.clone()).or_insert(StandupProject {
+                name,
+                minutes: 0,
+                descriptions: Vec::new(),
+            });
+            project.minutes += minutes;
+
+            if activity.description.is_empty() {
+                continue;
+            }
+            match project
+                .descriptions
+                .iter_mut()
+                .find(|(description, _)| *description == activity.description)
+            {
+                Some((_, description_minutes)) => *description_minutes += minutes,
+                None => project
+                    .descriptions
+                    .push((activity.description.clone(), minutes)),
+            }
+        }
+
+        let mut projects = projects.into_values().collect::<Vec<StandupProject>>();
+        projects.sort_by(|a, b| b.minutes.cmp(&a.minutes).then(a.name.cmp(&b.name)));
+
+        Some(Standup { date, projects })
+    }
+}
+
+impl Display for Standup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.date.format("%A %Y.%m.%d"))?;
+
+        for project in &self.projects {
+            write!(f, "- {} ({})", project.name, duration(project.minutes))?;
+            if !project.descriptions.is_empty() {
+                let descriptions = project
+                    .descriptions
+                    .iter()
+                    .map(|(description, minutes)| {
+                        if project.descriptions.len() == 1 {
+                            description.clone()
+                        } else {
+                            format!("{} ({})", description, duration(*minutes))
+                        }
+                    })
+                    .collect::<Vec<String>>();
+                write!(f, ": {}", descriptions.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Action;
+    use crate::test_utils::activity;
+
+    #[test]
+    fn test_standup() {
+        let activities = Activities(vec![
+            activity(25, (9, 0), (10, 0), Action::Code, &["rust"], "loader"),
+            activity(26, (9, 0), (10, 0), Action::Code, &["rust"], "loader"),
+            activity(26, (10, 0), (11, 0), Action::Code, &["rust"], "parser"),
+            activity(26, (11, 0), (12, 0), Action::Code, &["rust"], "loader"),
+            activity(
+                26,
+                (14, 0),
+                (15, 0),
+                Action::Code,
+                &["management"],
+                "weekly",
+            ),
+            activity(26, (15, 0), (16, 0), Action::Code, &[], "inbox"),
+            activity(28, (10, 0), (11, 0), Action::Code, &["rust"], "weekend"),
+        ]);
+
+        // monday 30th, the weekend and the empty friday are skipped
+        let standup =
+            Standup::new(&activities, &NaiveDate::from_ymd_opt(2022, 5, 30).unwrap()).unwrap();
+        assert_eq!(standup.date, NaiveDate::from_ymd_opt(2022, 5, 26).unwrap());
+        assert_eq!(
+            standup.to_string(),
+            "Thursday 2022.05.26
+- rust (3h00): loader (2h00), parser (1h00)
+- management (1h00): weekly
+- other (1h00): inbox
+"
+        );
+
+        assert_eq!(
+            Standup::new(&activities, &NaiveDate::from_ymd_opt(2022, 5, 25).unwrap()),
+            None
+        );
+    }
+}
@@ -0,0 +1,317 @@
+use crate::activity::Activities;
+use crate::graph::scale::Scale;
+use crate::graph::x_segments::ScaleXSegments;
+use chrono::{Datelike, Duration, NaiveDate};
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+
+// OTHER is the row of the activities without a project, it comes last
+const OTHER: &str = "other";
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum Rounding {
+    Up,
+    Down,
+    Nearest,
+}
+
+// RoundingTarget tells if each activity is rounded before being summed, or each cell of the grid
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum RoundingTarget {
+    Entry,
+    Cell,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct RoundingRule {
+    pub increment_minutes: i64,
+    pub rounding: Rounding,
+    pub target: RoundingTarget,
+}
+
+impl RoundingRule {
+    pub fn round(&self, minutes: i64) -> i64 {
+        if self.increment_minutes <= 1 {
+            return minutes;
+        }
+
+        let increments = match self.rounding {
+            Rounding::Up => {
+                (minutes + self.increment_minutes - 1).div_euclid(self.increment_minutes)
+            }
+            Rounding::Down => minutes.div_euclid(self.increment_minutes),
+            Rounding::Nearest => {
+                (minutes + self.increment_minutes / 2).div_euclid(self.increment_minutes)
+            }
+        };
+
+        increments * self.increment_minutes
+    }
+}
+
+// Timesheet is a grid of minutes with projects as rows and days as columns,
+// totals are sums of the rounded cells so they always add up. an activity with
+// several projects is counted once, in the row of its joined projects like rust,loader,
+// activities without a project go to the other row. the per project minutes of
+// YActivities are not used as they count a shared activity in each of its projects
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct Timesheet {
+    pub days: Vec<NaiveDate>,
+    pub rows: Vec<(String, Vec<i64>)>,
+}
+
+impl Timesheet {
+    // new builds the timesheet of the day, week or month containing date
+    pub fn new(
+        activities: &Activities,
+        scale: &Scale,
+        date: &NaiveDate,
+        rule: &RoundingRule,
+    ) -> Result<Timesheet> {
+        let period = match scale {
+            Scale::Day | Scale::Week | Scale::Month => {
+                ScaleXSegments::new(date, date).values[scale][0].clone()
+            }
+            _ => return Err(eyre!("timesheets are per day, week or month")),
+        };
+        let start_date = period.start_datetime.date();
+        let end_date = period.end_datetime.date() - Duration::days(1);
+
+        let mut period_activities = activities.filter(
+            &start_date,
+            &end_date,
+            &HashSet::new(),
+            &HashSet::new(),
+            &HashSet::new(),
+            &None,
+        );
+
+        if rule.target == RoundingTarget::Entry {
+            period_activities.0.iter_mut().for_each(|activity| {
+                let minutes = (activity.end_datetime - activity.start_datetime).num_minutes();
+                activity.end_datetime =
+                    activity.start_datetime + Duration::minutes(rule.round(minutes));
+            });
+        }
+
+        let days = start_date
+            .iter_days()
+            .take_while(|day| *day <= end_date)
+            .collect::<Vec<NaiveDate>>();
+
+        let mut per_project: HashMap<String, Vec<i64>> = HashMap::new();
+        for activity in &period_activities.0 {
+            let mut projects = activity.projects.iter().cloned().collect::<Vec<String>>();
+            projects.sort();
+            let project = if projects.is_empty() {
+                OTHER.to_string()
+            } else {
+                projects.join(",")
+            };
+
+            let day = (activity.start_datetime.date() - start_date).num_days() as usize;
+            per_project
+                .entry(project)
+                .or_insert_with(|| vec![0; days.len()])[day] +=
+                (activity.end_datetime - activity.start_datetime).num_minutes();
+        }
+
+        let mut rows = per_project
+            .into_iter()
+            .map(|(project, minutes)| {
+                let minutes = match rule.target {
+                    RoundingTarget::Cell => minutes.iter().map(|m| rule.round(*m)).collect(),
+                    RoundingTarget::Entry => minutes,
+                };
+                (project, minutes)
+            })
+            .filter(|(_, minutes): &(String, Vec<i64>)| minutes.iter().any(|m| *m != 0))
+            .collect::<Vec<(String, Vec<i64>)>>();
+        rows.sort_by(|a, b| (a.0 == OTHER, a).cmp(&(b.0 == OTHER, b)));
+
+        Ok(Timesheet { days, rows })
+    }
+
+    pub fn row_totals(&self) -> Vec<i64> {
+        self.rows
+            .iter()
+            .map(|(_, minutes)| minutes.iter().sum())
+            .collect()
+    }
+
+    pub fn day_totals(&self) -> Vec<i64> {
+        (0..self.days.len())
+            .map(|idx| self.rows.iter().map(|(_, minutes)| minutes[idx]).sum())
+            .collect()
+    }
+
+    pub fn total(&self) -> i64 {
+        self.row_totals().iter().sum()
+    }
+
+    pub fn to_csv<W: Write>(&self, writer: W) -> Result<()> {
+        let mut writer = ::csv::Writer::from_writer(writer);
+
+        writer.write_record(self.header())?;
+        for ((project, minutes), total) in self.rows.iter().zip(self.row_totals()) {
+            writer.write_record(row(project, minutes, total))?;
+        }
+        writer.write_record(row("total", &self.day_totals(), self.total()))?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let header = self.header();
+        let mut lines = vec![
+            format!("| {} |", header.join(" | ")),
+            format!("|{}", "---|".repeat(header.len())),
+        ];
+
+        for ((project, minutes), total) in self.rows.iter().zip(self.row_totals()) {
+            lines.push(format!("| {} |", row(project, minutes, total).join(" | ")));
+        }
+        lines.push(format!(
+            "| {} |",
+            row("**total**", &self.day_totals(), self.total()).join(" | ")
+        ));
+
+        lines.join("\n") + "\n"
+    }
+
+    fn header(&self) -> Vec<String> {
+        let mut header = vec!["project".to_string()];
+        header.extend(
+            self.days
+                .iter()
+                .map(|day| format!("{} {}", day.weekday(), day.format("%m-%d"))),
+        );
+        header.push("total".to_string());
+
+        header
+    }
+}
+
+fn row(label: &str, minutes: &[i64], total: i64) -> Vec<String> {
+    let mut row = vec![label.to_string()];
+    row.extend(minutes.iter().map(|m| hours(*m)));
+    row.push(hours(total));
+
+    row
+}
+
+// hours are written as decimals, 6 minute increments give exact tenths
+fn hours(minutes: i64) -> String {
+    format!("{:.2}", minutes as f64 / 60.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Action;
+    use crate::test_utils::activity;
+
+    #[test]
+    fn test_rounding_rule() {
+        let rule = |rounding| RoundingRule {
+            increment_minutes: 15,
+            rounding,
+            target: RoundingTarget::Cell,
+        };
+
+        assert_eq!(rule(Rounding::Up).round(16), 30);
+        assert_eq!(rule(Rounding::Up).round(15), 15);
+        assert_eq!(rule(Rounding::Down).round(29), 15);
+        assert_eq!(rule(Rounding::Nearest).round(22), 15);
+        assert_eq!(rule(Rounding::Nearest).round(23), 30);
+    }
+
+    #[test]
+    fn test_timesheet() {
+        let activities = Activities(vec![
+            activity(23, (9, 0), (9, 10), Action::Code, &["rust"], ""),
+            activity(23, (10, 0), (10, 10), Action::Code, &["rust"], ""),
+            activity(25, (14, 0), (15, 0), Action::Code, &["management"], ""),
+            activity(30, (14, 0), (15, 0), Action::Code, &["rust"], ""),
+            activity(26, (11, 0), (11, 30), Action::Meeting, &[], ""),
+        ]);
+        let mut shared = activity(27, (9, 0), (10, 0), Action::Code, &["rust"], "");
+        shared.projects.insert("management".to_string());
+        let activities = Activities(activities.0.into_iter().chain([shared]).collect());
+
+        let per_cell = Timesheet::new(
+            &activities,
+            &Scale::Week,
+            &NaiveDate::from_ymd_opt(2022, 5, 25).unwrap(),
+            &RoundingRule {
+                increment_minutes: 15,
+                rounding: Rounding::Up,
+                target: RoundingTarget::Cell,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(per_cell.days.len(), 7);
+        assert_eq!(
+            per_cell.rows,
+            vec![
+                ("management".to_string(), vec![0, 0, 60, 0, 0, 0, 0]),
+                ("management,rust".to_string(), vec![0, 0, 0, 0, 60, 0, 0]),
+                ("rust".to_string(), vec![30, 0, 0, 0, 0, 0, 0]),
+                ("other".to_string(), vec![0, 0, 0, 30, 0, 0, 0]),
+            ]
+        );
+        // the activity of two projects is only counted once
+        assert_eq!(per_cell.day_totals(), vec![30, 0, 60, 30, 60, 0, 0]);
+        assert_eq!(per_cell.total(), 180);
+
+        let per_entry = Timesheet::new(
+            &activities,
+            &Scale::Week,
+            &NaiveDate::from_ymd_opt(2022, 5, 25).unwrap(),
+            &RoundingRule {
+                increment_minutes: 6,
+                rounding: Rounding::Down,
+                target: RoundingTarget::Entry,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            per_entry.rows[2],
+            ("rust".to_string(), vec![12, 0, 0, 0, 0, 0, 0])
+        );
+
+        assert_eq!(
+            per_cell.to_markdown(),
+            "| project | Mon 05-23 | Tue 05-24 | Wed 05-25 | Thu 05-26 | Fri 05-27 | Sat 05-28 | Sun 05-29 | total |
+|---|---|---|---|---|---|---|---|---|
+| management | 0.00 | 0.00 | 1.00 | 0.00 | 0.00 | 0.00 | 0.00 | 1.00 |
+| management,rust | 0.00 | 0.00 | 0.00 | 0.00 | 1.00 | 0.00 | 0.00 | 1.00 |
+| rust | 0.50 | 0.00 | 0.00 | 0.00 | 0.00 | 0.00 | 0.00 | 0.50 |
+| other | 0.00 | 0.00 | 0.00 | 0.50 | 0.00 | 0.00 | 0.00 | 0.50 |
+| **total** | 0.50 | 0.00 | 1.00 | 0.50 | 1.00 | 0.00 | 0.00 | 3.00 |
+"
+        );
+
+        let mut out = Vec::new();
+        per_cell.to_csv(&mut out).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .ends_with("total,0.50,0.00,1.00,0.50,1.00,0.00,0.00,3.00\n"));
+
+        assert!(Timesheet::new(
+            &activities,
+            &Scale::All,
+            &NaiveDate::from_ymd_opt(2022, 5, 25).unwrap(),
+            &RoundingRule {
+                increment_minutes: 15,
+                rounding: Rounding::Up,
+                target: RoundingTarget::Cell,
+            },
+        )
+        .is_err());
+    }
+}
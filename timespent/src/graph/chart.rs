@@ -0,0 +1,349 @@
+use super::scale::{naive_date_to_scale_x, Scale};
+use super::x_segments::ScaleXSegments;
+use super::y_activities::YActivities;
+use eyre::{eyre, Result};
+#[cfg(feature = "png")]
+use resvg::{tiny_skia, usvg};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+const COLORS: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+const MARGIN_LEFT: f64 = 50.0;
+const MARGIN_RIGHT: f64 = 130.0;
+const MARGIN_TOP: f64 = 20.0;
+const MARGIN_BOTTOM: f64 = 40.0;
+const MAX_X_LABELS: usize = 12;
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum ChartKind {
+    StackedBar,
+    StackedArea,
+}
+
+// ChartSeries tells how the total minutes of each segment are split in the stack
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub enum ChartSeries {
+    Actions,
+    Projects,
+    Sources,
+}
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct Chart {
+    pub kind: ChartKind,
+    pub series: ChartSeries,
+    pub scale: Scale,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Chart {
+    // to_svg draws hours per x segment of the scale, series without any time are left out
+    pub fn to_svg(&self, sxs: &ScaleXSegments, y_activities: &YActivities) -> Result<String> {
+        let width = self.width as f64;
+        let height = self.height as f64;
+        let plot_width = width - MARGIN_LEFT - MARGIN_RIGHT;
+        let plot_height = height - MARGIN_TOP - MARGIN_BOTTOM;
+        if plot_width <= 0.0 || plot_height <= 0.0 {
+            return Err(eyre!(
+                "chart size {}x{} leaves no room to plot, it must be larger than {}x{}",
+                self.width,
+                self.height,
+                MARGIN_LEFT + MARGIN_RIGHT,
+                MARGIN_TOP + MARGIN_BOTTOM
+            ));
+        }
+
+        let segments = &sxs.values[&self.scale];
+        let series = self.series(y_activities);
+
+        // cumulated hours of each layer, the last layer is the top of the stack
+        let mut stacks: Vec<Vec<f64>> = Vec::new();
+        for (_, minutes) in &series {
+            let below = stacks.last().cloned().unwrap_or(vec![0.0; segments.len()]);
+            stacks.push(
+                below
+                    .iter()
+                    .zip(minutes)
+                    .map(|(below, minutes)| below + *minutes as f64 / 60.0)
+                    .collect(),
+            );
+        }
+        let max_hours = stacks
+            .last()
+            .map(|top| top.iter().cloned().fold(0.0, f64::max))
+            .unwrap_or(0.0);
+        let step = tick_step(max_hours);
+        let y_max = ((max_hours / step).ceil() * step).max(step);
+
+        let segment_width = plot_width / segments.len().max(1) as f64;
+        let x = |idx: usize| MARGIN_LEFT + idx as f64 * segment_width;
+        let y = |hours: f64| MARGIN_TOP + plot_height - hours / y_max * plot_height;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\" font-family=\"sans-serif\" font-size=\"11\">",
+            self.width, self.height, self.width, self.height
+        );
+        let _ = writeln!(
+            svg,
+            "<rect width=\"{}\" height=\"{}\" fill=\"white\"/>",
+            self.width, self.height
+        );
+
+        // y axis, with a grid line per tick
+        let mut tick = 0.0;
+        while tick <= y_max {
+            let _ = writeln!(
+                svg,
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#ddd\"/>",
+                MARGIN_LEFT,
+                y(tick),
+                MARGIN_LEFT + plot_width,
+                y(tick)
+            );
+            let _ = writeln!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}h</text>",
+                MARGIN_LEFT - 5.0,
+                y(tick) + 4.0,
+                tick
+            );
+            tick += step;
+        }
+
+        // stacked layers, drawn from the bottom
+        for (idx, top) in stacks.iter().enumerate() {
+            let color = COLORS[idx % COLORS.len()];
+            let bottom = if idx == 0 {
+                vec![0.0; segments.len()]
+            } else {
+                stacks[idx - 1].clone()
+            };
+
+            match self.kind {
+                ChartKind::StackedBar => {
+                    for (segment_idx, (top, bottom)) in top.iter().zip(&bottom).enumerate() {
+                        if top == bottom {
+                            continue;
+                        }
+                        let _ = writeln!(
+                            svg,
+                            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"/>",
+                            x(segment_idx) + segment_width * 0.1,
+                            y(*top),
+                            segment_width * 0.8,
+                            y(*bottom) - y(*top),
+                            color
+                        );
+                    }
+                }
+                ChartKind::StackedArea => {
+                    let center = |segment_idx: usize| x(segment_idx) + segment_width / 2.0;
+                    let points = top
+                        .iter()
+                        .enumerate()
+                        .map(|(segment_idx, hours)| (center(segment_idx), y(*hours)))
+                        .chain(
+                            bottom
+                                .iter()
+                                .enumerate()
+                                .rev()
+                                .map(|(segment_idx, hours)| (center(segment_idx), y(*hours))),
+                        )
+                        .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+                        .collect::<Vec<String>>();
+                    let _ = writeln!(
+                        svg,
+                        "<polygon points=\"{}\" fill=\"{}\"/>",
+                        points.join(" "),
+                        color
+                    );
+                }
+            }
+        }
+
+        // x axis labels, only some of them when there are many segments
+        let label_step = segments.len().div_ceil(MAX_X_LABELS);
+        for (idx, segment) in segments.iter().enumerate().step_by(label_step.max(1)) {
+            let _ = writeln!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+                x(idx) + segment_width / 2.0,
+                MARGIN_TOP + plot_height + 15.0,
+                escape(&naive_date_to_scale_x(
+                    &segment.start_datetime.date(),
+                    &self.scale
+                ))
+            );
+        }
+
+        // legend, top of the stack first like the chart
+        for (idx, (name, _)) in series.iter().enumerate().rev() {
+            let row = (series.len() - 1 - idx) as f64;
+            let _ = writeln!(
+                svg,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"10\" height=\"10\" fill=\"{}\"/>",
+                width - MARGIN_RIGHT + 15.0,
+                MARGIN_TOP + row * 16.0,
+                COLORS[idx % COLORS.len()]
+            );
+            let _ = writeln!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\">{}</text>",
+                width - MARGIN_RIGHT + 30.0,
+                MARGIN_TOP + row * 16.0 + 9.0,
+                escape(name)
+            );
+        }
+
+        svg.push_str("</svg>\n");
+
+        Ok(svg)
+    }
+
+    // to_png rasterizes the svg, labels use the fonts installed on the system
+    #[cfg(feature = "png")]
+    pub fn to_png(&self, sxs: &ScaleXSegments, y_activities: &YActivities) -> Result<Vec<u8>> {
+        let mut options = usvg::Options::default();
+        options.fontdb_mut().load_system_fonts();
+        let tree = usvg::Tree::from_str(&self.to_svg(sxs, y_activities)?, &options)?;
+
+        let mut pixmap = tiny_skia::Pixmap::new(self.width, self.height)
+            .ok_or_else(|| eyre!("invalid chart size {}x{}", self.width, self.height))?;
+        resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+        Ok(pixmap.encode_png()?)
+    }
+
+    fn series(&self, y_activities: &YActivities) -> Vec<(String, Vec<i64>)> {
+        let mut series = match self.series {
+            ChartSeries::Actions => y_activities.scale_actions_total_minutes[&self.scale]
+                .iter()
+                .map(|(action, minutes)| (action.to_string(), minutes.clone()))
+                .collect::<Vec<(String, Vec<i64>)>>(),
+            ChartSeries::Projects => y_activities.projects_stack(&self.scale),
+            ChartSeries::Sources => y_activities.scale_sources_total_minutes[&self.scale]
+                .iter()
+                .map(|(source, minutes)| (source.clone(), minutes.clone()))
+                .collect(),
+        };
+        series.retain(|(_, minutes)| minutes.iter().any(|m| *m != 0));
+        series.sort();
+
+        series
+    }
+}
+
+// tick_step gives a round number of hours so the y axis has at most 5 ticks
+fn tick_step(max_hours: f64) -> f64 {
+    let mut magnitude = 1.0;
+    loop {
+        for step in [1.0, 2.0, 5.0] {
+            if max_hours / (step * magnitude) <= 5.0 {
+                return step * magnitude;
+            }
+        }
+        magnitude *= 10.0;
+    }
+}
+
+fn escape(input: &str) -> String {
+    input
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::{Action, Activities};
+    use crate::test_utils::activity;
+
+    #[test]
+    fn test_chart() {
+        let activities = Activities(vec![
+            activity(23, (9, 0), (12, 0), Action::Code, &["rust"], ""),
+            activity(24, (9, 0), (11, 0), Action::Meeting, &["rust"], ""),
+            activity(25, (9, 0), (13, 0), Action::Code, &["rust"], ""),
+        ]);
+        let agg = activities.aggregate_all();
+        let sxs = ScaleXSegments::new(&agg.0, &agg.1);
+        let y_activities = YActivities::new(&activities, &agg.2, &agg.3, &agg.4, &sxs);
+
+        let mut chart = Chart {
+            kind: ChartKind::StackedBar,
+            series: ChartSeries::Actions,
+            scale: Scale::Day,
+            width: 400,
+            height: 200,
+        };
+
+        let svg = chart.to_svg(&sxs, &y_activities).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert_eq!(svg.matches("<rect ").count(), 1 + 3 + 2);
+        assert!(svg.contains(">2022-05-24</text>"));
+        assert!(svg.contains(">meeting</text>"));
+
+        chart.kind = ChartKind::StackedArea;
+        let svg = chart.to_svg(&sxs, &y_activities).unwrap();
+        assert_eq!(svg.matches("<polygon ").count(), 2);
+
+        #[cfg(feature = "png")]
+        {
+            let png = chart.to_png(&sxs, &y_activities).unwrap();
+            assert_eq!(&png[1..4], b"PNG");
+        }
+
+        // the margins alone take 180x60
+        chart.width = 180;
+        assert!(chart.to_svg(&sxs, &y_activities).is_err());
+        chart.width = 400;
+        chart.height = 50;
+        assert!(chart.to_svg(&sxs, &y_activities).is_err());
+        #[cfg(feature = "png")]
+        assert!(chart.to_png(&sxs, &y_activities).is_err());
+    }
+
+    #[test]
+    fn test_projects_series() {
+        let activities = Activities(vec![
+            activity(23, (9, 0), (11, 0), Action::Code, &["rust", "loader"], ""),
+            activity(23, (11, 0), (12, 0), Action::Meeting, &[], ""),
+        ]);
+        let agg = activities.aggregate_all();
+        let sxs = ScaleXSegments::new(&agg.0, &agg.1);
+        let y_activities = YActivities::new(&activities, &agg.2, &agg.3, &agg.4, &sxs);
+
+        let chart = Chart {
+            kind: ChartKind::StackedBar,
+            series: ChartSeries::Projects,
+            scale: Scale::Day,
+            width: 400,
+            height: 200,
+        };
+
+        // the shared activity is split so the stack is as high as the total
+        assert_eq!(
+            chart.series(&y_activities),
+            vec![
+                ("loader".to_string(), vec![60]),
+                ("other".to_string(), vec![60]),
+                ("rust".to_string(), vec![60]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tick_step() {
+        assert_eq!(tick_step(0.0), 1.0);
+        assert_eq!(tick_step(4.0), 1.0);
+        assert_eq!(tick_step(9.0), 2.0);
+        assert_eq!(tick_step(160.0), 50.0);
+    }
+}
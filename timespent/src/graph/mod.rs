@@ -1,4 +1,6 @@
+pub mod chart;
 pub mod scale;
 pub mod ui;
+pub mod vega;
 pub mod x_segments;
 pub mod y_activities;
@@ -13,9 +13,57 @@ pub struct Filter {
     pub max_date: NaiveDate,
     pub actions: HashSet<Action>,
     pub projects: HashSet<String>,
+    #[serde(default)]
+    pub sources: HashSet<String>,
     pub description: Option<String>,
 }
 
+impl Filter {
+    pub fn apply(&self, activities: &Activities) -> Activities {
+        activities.filter(
+            &self.min_date,
+            &self.max_date,
+            &self.actions,
+            &self.projects,
+            &self.sources,
+            &self.description,
+        )
+    }
+
+    // evaluate computes what the filter shows of the activities without any shared state,
+    // so each view or client can keep its own filter
+    pub fn evaluate(&self, activities: &Activities) -> FilteredGraph {
+        let filtered_activities = self.apply(activities);
+        let activities_aggregate = filtered_activities.aggregate_all();
+
+        let per_scale_x_segments = ScaleXSegments::new(&self.min_date, &self.max_date);
+        let per_scale_y_activities = YActivities::new(
+            &filtered_activities,
+            &activities_aggregate.2,
+            &activities_aggregate.3,
+            &activities_aggregate.4,
+            &per_scale_x_segments,
+        );
+
+        FilteredGraph {
+            filter: self.clone(),
+            activities_aggregate,
+            per_scale_x_segments,
+            per_scale_y_activities,
+        }
+    }
+}
+
+// FilteredGraph is the result of a filter on the activities
+#[derive(PartialEq, Eq, Debug, Deserialize, Serialize, Clone, TS)]
+#[ts(export)]
+pub struct FilteredGraph {
+    pub filter: Filter,
+    pub activities_aggregate: ActivitiesAggregate,
+    pub per_scale_x_segments: ScaleXSegments,
+    pub per_scale_y_activities: YActivities,
+}
+
 // Aggregates
 // for each scale, we need the x-axis and a y-axis for each of the type of activities
 #[derive(PartialEq, Eq, Debug, Deserialize, Serialize, Clone, TS)]
@@ -40,6 +88,7 @@ impl Graph {
             max_date: filtered_activities_aggregate.1,
             actions: filtered_activities_aggregate.2.clone(),
             projects: filtered_activities_aggregate.3.clone(),
+            sources: filtered_activities_aggregate.4.clone(),
             description: None,
         };
 
@@ -55,6 +104,7 @@ impl Graph {
             all_activities,
             &filtered_activities_aggregate.2,
             &filtered_activities_aggregate.3,
+            &filtered_activities_aggregate.4,
             &filtered_per_scale_x_segments,
         );
 
@@ -69,26 +119,18 @@ impl Graph {
         }
     }
 
-    pub fn apply_filter(&mut self, filter: &Filter) {
-        let filtered_activities = self.all_activities.filter(
-            &filter.min_date,
-            &filter.max_date,
-            &filter.actions,
-            &filter.projects,
-            &filter.description,
-        );
+    // query leaves the graph as it is, unlike apply_filter
+    pub fn query(&self, filter: &Filter) -> FilteredGraph {
+        filter.evaluate(&self.all_activities)
+    }
 
-        self.filtered_activities_aggregate = filtered_activities.aggregate_all();
+    pub fn apply_filter(&mut self, filter: &Filter) {
+        let filtered = self.query(filter);
 
-        self.applied_filter = filter.clone();
-        self.filtered_per_scale_x_segments =
-            ScaleXSegments::new(&filter.min_date, &filter.max_date);
-        self.filtered_per_scale_y_activities = YActivities::new(
-            &filtered_activities,
-            &self.filtered_activities_aggregate.2,
-            &self.filtered_activities_aggregate.3,
-            &self.filtered_per_scale_x_segments,
-        );
+        self.applied_filter = filtered.filter;
+        self.filtered_activities_aggregate = filtered.activities_aggregate;
+        self.filtered_per_scale_x_segments = filtered.per_scale_x_segments;
+        self.filtered_per_scale_y_activities = filtered.per_scale_y_activities;
     }
 }
 
@@ -96,6 +138,7 @@ impl Graph {
 mod tests {
     use super::*;
     use crate::activity::Activity;
+    use crate::graph::scale::Scale;
 
     #[test]
     fn test_new_graph() {
@@ -112,6 +155,8 @@ mod tests {
                 description: "description".to_string(),
                 action: Action::Code,
                 projects: ["tag2".to_string(), "tag1".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },
             Activity {
                 start_datetime: NaiveDate::from_ymd_opt(2022, 7, 25)
@@ -125,6 +170,8 @@ mod tests {
                 description: "description".to_string(),
                 action: Action::Review,
                 projects: ["tag2".to_string(), "tag3".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },
         ]);
 
@@ -134,6 +181,7 @@ mod tests {
             NaiveDate::from_ymd_opt(2022, 7, 25).unwrap(),
             [Action::Code, Action::Review].into(),
             ["tag1".to_string(), "tag2".to_string(), "tag3".to_string()].into(),
+            ["work".to_string()].into(),
         );
 
         let filter = Filter {
@@ -141,6 +189,7 @@ mod tests {
             max_date: NaiveDate::from_ymd_opt(2022, 7, 25).unwrap(),
             actions: [Action::Code, Action::Review].into(),
             projects: ["tag1".to_string(), "tag2".to_string(), "tag3".to_string()].into(),
+            sources: ["work".to_string()].into(),
             description: None,
         };
 
@@ -149,7 +198,7 @@ mod tests {
             &NaiveDate::from_ymd_opt(2022, 7, 25).unwrap(),
         );
 
-        let y_act = YActivities::new(&activities, &act_agg.2, &act_agg.3, &sxs);
+        let y_act = YActivities::new(&activities, &act_agg.2, &act_agg.3, &act_agg.4, &sxs);
 
         assert_eq!(graph.all_activities, activities, "all activities");
         assert_eq!(graph.filtered_activities_aggregate, act_agg, "activity agg");
@@ -173,6 +222,8 @@ mod tests {
                 description: "act 1".to_string(),
                 action: Action::Code,
                 projects: ["tag1".to_string(), "tag2".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },
             Activity {
                 start_datetime: NaiveDate::from_ymd_opt(2022, 7, 25)
@@ -186,6 +237,8 @@ mod tests {
                 description: "act".to_string(),
                 action: Action::Review,
                 projects: ["tag2".to_string(), "tag3".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },
         ]);
 
@@ -196,6 +249,7 @@ mod tests {
             max_date: NaiveDate::from_ymd_opt(2022, 7, 22).unwrap(),
             actions: [Action::Code, Action::Review].into(),
             projects: ["tag2".to_string()].into(),
+            sources: ["work".to_string()].into(),
             description: None,
         };
         graph.apply_filter(&filter);
@@ -209,11 +263,18 @@ mod tests {
             &NaiveDate::from_ymd_opt(2022, 7, 22).unwrap(),
             &[Action::Code].into(),
             &["tag2".to_string()].into(),
+            &["work".to_string()].into(),
             &None,
         );
         let act_agg = filtered_activities.aggregate_all();
 
-        let y_act = YActivities::new(&filtered_activities, &act_agg.2, &act_agg.3, &sxs);
+        let y_act = YActivities::new(
+            &filtered_activities,
+            &act_agg.2,
+            &act_agg.3,
+            &act_agg.4,
+            &sxs,
+        );
 
         assert_eq!(graph.all_activities, activities, "all activities");
         assert_eq!(graph.filtered_activities_aggregate, act_agg, "activity agg");
@@ -221,4 +282,101 @@ mod tests {
         assert_eq!(graph.filtered_per_scale_x_segments, sxs, "x segments");
         assert_eq!(graph.filtered_per_scale_y_activities, y_act, "y activities");
     }
+
+    #[test]
+    fn test_filter_without_sources() {
+        let filter: Filter = serde_json::from_str(
+            r#"{
+                "min_date": "2022-07-22",
+                "max_date": "2022-07-22",
+                "actions": ["Code"],
+                "projects": ["tag1"],
+                "description": null
+            }"#,
+        )
+        .unwrap();
+        assert!(filter.sources.is_empty());
+
+        let activities = Activities(vec![Activity {
+            start_datetime: NaiveDate::from_ymd_opt(2022, 7, 22)
+                .unwrap()
+                .and_hms_opt(12, 0, 0)
+                .unwrap(),
+            end_datetime: NaiveDate::from_ymd_opt(2022, 7, 22)
+                .unwrap()
+                .and_hms_opt(13, 0, 0)
+                .unwrap(),
+            description: "act 1".to_string(),
+            action: Action::Code,
+            projects: ["tag1".to_string()].into(),
+            source: "work".to_string(),
+            id: None,
+        }]);
+        assert_eq!(filter.apply(&activities), activities);
+    }
+
+    #[test]
+    fn test_graph_query() {
+        let activities = Activities(vec![
+            Activity {
+                start_datetime: NaiveDate::from_ymd_opt(2022, 7, 22)
+                    .unwrap()
+                    .and_hms_opt(12, 0, 0)
+                    .unwrap(),
+                end_datetime: NaiveDate::from_ymd_opt(2022, 7, 22)
+                    .unwrap()
+                    .and_hms_opt(13, 0, 0)
+                    .unwrap(),
+                description: "act 1".to_string(),
+                action: Action::Code,
+                projects: ["tag1".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
+            },
+            Activity {
+                start_datetime: NaiveDate::from_ymd_opt(2022, 7, 25)
+                    .unwrap()
+                    .and_hms_opt(12, 0, 0)
+                    .unwrap(),
+                end_datetime: NaiveDate::from_ymd_opt(2022, 7, 25)
+                    .unwrap()
+                    .and_hms_opt(13, 0, 0)
+                    .unwrap(),
+                description: "act 2".to_string(),
+                action: Action::Review,
+                projects: ["tag2".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
+            },
+        ]);
+
+        let graph = Graph::new(&activities);
+        let mut filter = graph.all_filter.clone();
+        filter.actions = [Action::Review].into();
+
+        let filtered = graph.query(&filter);
+        assert_eq!(filtered, filter.evaluate(&activities), "pure");
+        assert_eq!(graph, Graph::new(&activities), "graph unchanged");
+        assert_eq!(filtered.activities_aggregate.3, ["tag2".to_string()].into());
+        assert_eq!(
+            filtered.per_scale_y_activities.scale_total_minutes[&Scale::All],
+            vec![60]
+        );
+
+        let mut applied = graph.clone();
+        applied.apply_filter(&filter);
+        assert_eq!(applied.applied_filter, filtered.filter);
+        assert_eq!(
+            applied.filtered_activities_aggregate,
+            filtered.activities_aggregate
+        );
+        assert_eq!(
+            applied.filtered_per_scale_x_segments,
+            filtered.per_scale_x_segments
+        );
+        assert_eq!(
+            applied.filtered_per_scale_y_activities,
+            filtered.per_scale_y_activities
+        );
+    }
 }
@@ -0,0 +1,177 @@
+use super::scale::{naive_date_to_scale_x, Scale};
+use super::ui::Graph;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use ts_rs::TS;
+
+const SCHEMA: &str = "https://vega.github.io/schema/vega-lite/v5.json";
+
+#[derive(Eq, PartialEq, Debug, Deserialize, Serialize, Clone, Copy, TS)]
+#[ts(export)]
+pub enum VegaSpec {
+    ActionsArea,
+    ProjectsArea,
+    Totals,
+}
+
+impl Graph {
+    // vega_lite builds a self-contained vega-lite spec of the filtered data for the scale,
+    // values are inlined so the spec can be rendered anywhere as is
+    pub fn vega_lite(&self, spec: &VegaSpec, scale: &Scale) -> Value {
+        let segments = &self.filtered_per_scale_x_segments.values[scale];
+        let y_activities = &self.filtered_per_scale_y_activities;
+
+        let mut series = match spec {
+            VegaSpec::ActionsArea => y_activities.scale_actions_total_minutes[scale]
+                .iter()
+                .map(|(action, minutes)| (action.to_string(), minutes.clone()))
+                .collect::<Vec<(String, Vec<i64>)>>(),
+            VegaSpec::ProjectsArea => y_activities.projects_stack(scale),
+            VegaSpec::Totals => vec![(
+                "total".to_string(),
+                y_activities.scale_total_minutes[scale].clone(),
+            )],
+        };
+        series.retain(|(_, minutes)| minutes.iter().any(|m| *m != 0));
+        series.sort();
+
+        let label = |date: &NaiveDate| naive_date_to_scale_x(date, scale);
+        let values = series
+            .iter()
+            .flat_map(|(name, minutes)| {
+                segments
+                    .iter()
+                    .zip(minutes.iter())
+                    .map(move |(segment, minutes)| {
+                        let date = segment.start_datetime.date();
+                        json!({
+                            "segment": label(&date),
+                            "start": date.to_string(),
+                            "series": name,
+                            "hours": *minutes as f64 / 60.0,
+                        })
+                    })
+            })
+            .collect::<Vec<Value>>();
+
+        // segments keep their chronological order on the x axis
+        let x = json!({
+            "field": "segment",
+            "type": "ordinal",
+            "sort": segments
+                .iter()
+                .map(|segment| label(&segment.start_datetime.date()))
+                .collect::<Vec<String>>(),
+            "title": null,
+        });
+        let y = json!({
+            "field": "hours",
+            "type": "quantitative",
+            "title": "hours",
+        });
+
+        let (mark, encoding) = match spec {
+            VegaSpec::ActionsArea | VegaSpec::ProjectsArea => (
+                json!({ "type": "area", "interpolate": "monotone" }),
+                json!({
+                    "x": x,
+                    "y": {
+                        "field": "hours",
+                        "type": "quantitative",
+                        "title": "hours",
+                        "stack": "zero",
+                    },
+                    "color": {
+                        "field": "series",
+                        "type": "nominal",
+                        "title": if *spec == VegaSpec::ActionsArea { "action" } else { "project" },
+                    },
+                    "tooltip": [
+                        { "field": "series", "type": "nominal" },
+                        { "field": "start", "type": "temporal" },
+                        { "field": "hours", "type": "quantitative", "format": ".2f" },
+                    ],
+                }),
+            ),
+            VegaSpec::Totals => (
+                json!({ "type": "bar" }),
+                json!({
+                    "x": x,
+                    "y": y,
+                    "tooltip": [
+                        { "field": "start", "type": "temporal" },
+                        { "field": "hours", "type": "quantitative", "format": ".2f" },
+                    ],
+                }),
+            ),
+        };
+
+        json!({
+            "$schema": SCHEMA,
+            "width": "container",
+            "data": { "values": values },
+            "mark": mark,
+            "encoding": encoding,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::{Action, Activities};
+    use crate::test_utils::activity;
+
+    #[test]
+    fn test_vega_lite() {
+        let graph = Graph::new(&Activities(vec![
+            activity(23, (9, 0), (10, 30), Action::Code, &["rust"], ""),
+            activity(24, (9, 0), (10, 30), Action::Meeting, &["management"], ""),
+            activity(24, (9, 0), (10, 30), Action::Code, &["rust"], ""),
+        ]));
+
+        let spec = graph.vega_lite(&VegaSpec::ActionsArea, &Scale::Day);
+        assert_eq!(spec["$schema"], SCHEMA);
+        assert_eq!(spec["mark"]["type"], "area");
+        assert_eq!(spec["encoding"]["y"]["stack"], "zero");
+        assert_eq!(
+            spec["encoding"]["x"]["sort"],
+            json!(["2022-05-23", "2022-05-24"])
+        );
+        let values = spec["data"]["values"].as_array().unwrap();
+        assert_eq!(values.len(), 4);
+        assert_eq!(
+            values[1],
+            json!({
+                "segment": "2022-05-24",
+                "start": "2022-05-24",
+                "series": "code",
+                "hours": 1.5,
+            })
+        );
+
+        let spec = graph.vega_lite(&VegaSpec::Totals, &Scale::Week);
+        assert_eq!(spec["mark"]["type"], "bar");
+        assert_eq!(spec["data"]["values"][0]["hours"], 4.5);
+
+        // the activity shared by two projects is split so the stack is as high as the total
+        let graph = Graph::new(&Activities(vec![
+            activity(23, (9, 0), (11, 0), Action::Code, &["rust", "loader"], ""),
+            activity(23, (11, 0), (12, 0), Action::Meeting, &[], ""),
+        ]));
+        let spec = graph.vega_lite(&VegaSpec::ProjectsArea, &Scale::Day);
+        assert_eq!(
+            spec["data"]["values"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|value| (
+                    value["series"].as_str().unwrap(),
+                    value["hours"].as_f64().unwrap()
+                ))
+                .collect::<Vec<(&str, f64)>>(),
+            vec![("loader", 1.0), ("other", 1.0), ("rust", 1.0)]
+        );
+    }
+}
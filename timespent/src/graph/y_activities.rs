@@ -11,6 +11,9 @@ pub struct YActivities {
     pub scale_total_minutes: HashMap<Scale, Vec<i64>>,
     pub scale_actions_total_minutes: HashMap<Scale, HashMap<Action, Vec<i64>>>,
     pub scale_projects_total_minutes: HashMap<Scale, HashMap<String, Vec<i64>>>,
+    // minutes of an activity split evenly between its projects, they can be stacked
+    pub scale_projects_split_minutes: HashMap<Scale, HashMap<String, Vec<i64>>>,
+    pub scale_sources_total_minutes: HashMap<Scale, HashMap<String, Vec<i64>>>,
 }
 
 impl YActivities {
@@ -18,6 +21,7 @@ impl YActivities {
         activities: &Activities,
         actions: &HashSet<Action>,
         projects: &HashSet<String>,
+        sources: &HashSet<String>,
         sxs: &ScaleXSegments,
     ) -> Self {
         // init each scale/xsegments with a vector of 0s
@@ -54,6 +58,21 @@ impl YActivities {
                 )
             })
             .collect();
+        let mut scale_projects_split_minutes = scale_projects_total_minutes.clone();
+
+        let mut scale_sources_total_minutes: HashMap<Scale, HashMap<String, Vec<i64>>> = sxs
+            .values
+            .iter()
+            .map(|(scale, segments)| {
+                (
+                    *scale,
+                    sources
+                        .iter()
+                        .map(|source| (source.clone(), vec![0i64; segments.len()]))
+                        .collect(),
+                )
+            })
+            .collect();
 
         // loop through activities to fill in the right scale/xsegment
         activities.0.iter().for_each(|activity| {
@@ -86,6 +105,29 @@ impl YActivities {
                         .get_mut(project)
                         .unwrap()[curr_idx] += curr_activity_time
                 });
+
+                // split the time between the projects, the first ones sorted by name
+                // get the minutes left so the split adds up to the time of the activity
+                let mut projects = activity.projects.iter().collect::<Vec<&String>>();
+                projects.sort();
+                if !projects.is_empty() {
+                    let share = curr_activity_time.div_euclid(projects.len() as i64);
+                    let rest = curr_activity_time.rem_euclid(projects.len() as i64);
+                    projects.iter().enumerate().for_each(|(idx, project)| {
+                        scale_projects_split_minutes
+                            .get_mut(&scale)
+                            .unwrap()
+                            .get_mut(*project)
+                            .unwrap()[curr_idx] += share + ((idx as i64) < rest) as i64
+                    });
+                }
+
+                // add the time for this activity to the source total minutes
+                scale_sources_total_minutes
+                    .get_mut(&scale)
+                    .unwrap()
+                    .get_mut(&activity.source)
+                    .unwrap()[curr_idx] += curr_activity_time;
             });
         });
 
@@ -93,7 +135,33 @@ impl YActivities {
             scale_total_minutes,
             scale_actions_total_minutes,
             scale_projects_total_minutes,
+            scale_projects_split_minutes,
+            scale_sources_total_minutes,
+        }
+    }
+
+    // projects_stack gives the split minutes of each project of the scale, with the minutes
+    // of the activities without a project as other, so the stacked layers add up to the total
+    pub fn projects_stack(&self, scale: &Scale) -> Vec<(String, Vec<i64>)> {
+        let mut stack = self.scale_projects_split_minutes[scale]
+            .iter()
+            .map(|(project, minutes)| (project.clone(), minutes.clone()))
+            .collect::<Vec<(String, Vec<i64>)>>();
+
+        let other = self.scale_total_minutes[scale]
+            .iter()
+            .enumerate()
+            .map(|(idx, total)| total - stack.iter().map(|(_, minutes)| minutes[idx]).sum::<i64>())
+            .collect::<Vec<i64>>();
+        match stack.iter_mut().find(|(project, _)| project == "other") {
+            Some((_, minutes)) => minutes
+                .iter_mut()
+                .zip(other)
+                .for_each(|(minutes, other)| *minutes += other),
+            None => stack.push(("other".to_string(), other)),
         }
+
+        stack
     }
 
     // pub fn filter_by_date(&self, start_date: &NaiveDate, end_date: &NaiveDate) -> YActivities {
@@ -136,6 +204,8 @@ mod tests {
                 description: "activity 1".to_string(),
                 action: Action::Code,
                 projects: ["tag1".to_string(), "tag2".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },
             Activity {
                 start_datetime: NaiveDate::from_ymd_opt(2022, 7, 21)
@@ -149,6 +219,8 @@ mod tests {
                 description: "activity 1".to_string(),
                 action: Action::Code,
                 projects: ["tag1".to_string(), "tag2".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },
             Activity {
                 start_datetime: NaiveDate::from_ymd_opt(2022, 7, 22)
@@ -162,6 +234,8 @@ mod tests {
                 description: "activity 1".to_string(),
                 action: Action::Code,
                 projects: ["tag3".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },
             Activity {
                 start_datetime: NaiveDate::from_ymd_opt(2022, 7, 22)
@@ -175,6 +249,8 @@ mod tests {
                 description: "activity 1".to_string(),
                 action: Action::Code,
                 projects: ["tag1".to_string(), "tag2".to_string()].into(),
+                source: "work".to_string(),
+                id: None,
             },
             Activity {
                 start_datetime: NaiveDate::from_ymd_opt(2022, 7, 25)
@@ -188,6 +264,8 @@ mod tests {
                 description: "activity 2".to_string(),
                 action: Action::Review,
                 projects: ["tag2".to_string(), "tag3".to_string()].into(),
+                source: "team".to_string(),
+                id: None,
             },
         ]);
 
@@ -195,8 +273,13 @@ mod tests {
 
         let sxs = ScaleXSegments::new(&agg_activities.0, &agg_activities.1);
 
-        let y_activities =
-            YActivities::new(&activities, &agg_activities.2, &agg_activities.3, &sxs);
+        let y_activities = YActivities::new(
+            &activities,
+            &agg_activities.2,
+            &agg_activities.3,
+            &agg_activities.4,
+            &sxs,
+        );
 
         assert_eq!(
             y_activities.scale_total_minutes[&Scale::Day],
@@ -245,5 +328,29 @@ mod tests {
             y_activities.scale_projects_total_minutes[&Scale::Day]["tag3"],
             vec![0, 0, 60, 0, 0, 60]
         );
+        assert_eq!(
+            y_activities.scale_projects_split_minutes[&Scale::Day]["tag1"],
+            vec![30, 30, 30, 0, 0, 0]
+        );
+        assert_eq!(
+            y_activities.scale_projects_split_minutes[&Scale::Day]["tag3"],
+            vec![0, 0, 60, 0, 0, 30]
+        );
+        let stack = y_activities.projects_stack(&Scale::Day);
+        assert!(stack.contains(&("other".to_string(), vec![0; 6])));
+        assert_eq!(
+            (0..6)
+                .map(|idx| stack.iter().map(|(_, minutes)| minutes[idx]).sum::<i64>())
+                .collect::<Vec<i64>>(),
+            y_activities.scale_total_minutes[&Scale::Day]
+        );
+        assert_eq!(
+            y_activities.scale_sources_total_minutes[&Scale::Day]["work"],
+            vec![60, 60, 120, 0, 0, 0]
+        );
+        assert_eq!(
+            y_activities.scale_sources_total_minutes[&Scale::Day]["team"],
+            vec![0, 0, 0, 0, 0, 60]
+        );
     }
 }
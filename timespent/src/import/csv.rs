@@ -0,0 +1,297 @@
+use crate::activity::{Activities, Activity};
+use crate::parser::tags_to_types;
+use ::csv::{ReaderBuilder, StringRecord};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+
+// CsvMapping tells which columns of a csv export hold the parts of an activity,
+// the end is taken from the end columns when set, from the duration otherwise
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct CsvMapping {
+    pub source: String,
+    pub start_date: String,
+    pub start_time: String,
+    pub end_date: Option<String>,
+    pub end_time: Option<String>,
+    pub duration: Option<String>,
+    pub project: Option<String>,
+    pub tags: Option<String>,
+    pub tag_separator: char,
+    pub description: Option<String>,
+    pub date_format: String,
+    pub time_format: String,
+}
+
+impl CsvMapping {
+    pub fn toggl() -> CsvMapping {
+        CsvMapping {
+            source: "toggl".to_string(),
+            start_date: "Start date".to_string(),
+            start_time: "Start time".to_string(),
+            end_date: Some("End date".to_string()),
+            end_time: Some("End time".to_string()),
+            duration: Some("Duration".to_string()),
+            project: Some("Project".to_string()),
+            tags: Some("Tags".to_string()),
+            tag_separator: ',',
+            description: Some("Description".to_string()),
+            date_format: "%Y-%m-%d".to_string(),
+            time_format: "%H:%M:%S".to_string(),
+        }
+    }
+
+    pub fn clockify() -> CsvMapping {
+        CsvMapping {
+            source: "clockify".to_string(),
+            start_date: "Start Date".to_string(),
+            start_time: "Start Time".to_string(),
+            end_date: Some("End Date".to_string()),
+            end_time: Some("End Time".to_string()),
+            duration: Some("Duration (h)".to_string()),
+            project: Some("Project".to_string()),
+            tags: Some("Tags".to_string()),
+            tag_separator: ',',
+            description: Some("Description".to_string()),
+            date_format: "%m/%d/%Y".to_string(),
+            time_format: "%I:%M:%S %p".to_string(),
+        }
+    }
+}
+
+pub fn import_csv_file(path: &str, mapping: &CsvMapping) -> Result<Activities> {
+    import_csv(File::open(path)?, mapping)
+}
+
+pub fn import_csv<R: Read>(reader: R, mapping: &CsvMapping) -> Result<Activities> {
+    let mut reader = ReaderBuilder::new().flexible(true).from_reader(reader);
+    let columns = Columns::new(reader.headers()?, mapping)?;
+
+    let mut activities = Vec::new();
+    for (idx, record) in reader.records().enumerate() {
+        let record = record?;
+        match columns.activity(&record, mapping) {
+            Ok(activity) => activities.push(activity),
+            Err(err) => eprintln!("can not import record {}: {:?} {}", idx + 1, record, err),
+        }
+    }
+
+    Ok(activities.into())
+}
+
+// column indexes, looked up once from the header
+struct Columns {
+    start_date: usize,
+    start_time: usize,
+    end_date: Option<usize>,
+    end_time: Option<usize>,
+    duration: Option<usize>,
+    project: Option<usize>,
+    tags: Option<usize>,
+    description: Option<usize>,
+}
+
+impl Columns {
+    fn new(headers: &StringRecord, mapping: &CsvMapping) -> Result<Columns> {
+        let find = |name: &str| headers.iter().position(|header| header.trim() == name);
+        let find_opt = |name: &Option<String>| name.as_ref().and_then(|name| find(name));
+
+        let columns = Columns {
+            start_date: find(&mapping.start_date)
+                .ok_or_else(|| eyre!("missing column {}", mapping.start_date))?,
+            start_time: find(&mapping.start_time)
+                .ok_or_else(|| eyre!("missing column {}", mapping.start_time))?,
+            end_date: find_opt(&mapping.end_date),
+            end_time: find_opt(&mapping.end_time),
+            duration: find_opt(&mapping.duration),
+            project: find_opt(&mapping.project),
+            tags: find_opt(&mapping.tags),
+            description: find_opt(&mapping.description),
+        };
+
+        if columns.end_time.is_none() && columns.duration.is_none() {
+            return Err(eyre!("neither end time nor duration column found"));
+        }
+
+        Ok(columns)
+    }
+
+    fn activity(&self, record: &StringRecord, mapping: &CsvMapping) -> Result<Activity> {
+        let field = |idx: usize| record.get(idx).unwrap_or("").trim();
+        let field_opt = |idx: Option<usize>| idx.map(field).filter(|value| !value.is_empty());
+
+        let start_date = NaiveDate::parse_from_str(field(self.start_date), &mapping.date_format)?;
+        let start_datetime = start_date.and_time(NaiveTime::parse_from_str(
+            field(self.start_time),
+            &mapping.time_format,
+        )?);
+
+        let end_datetime = match (field_opt(self.end_time), field_opt(self.duration)) {
+            (Some(end_time), _) => {
+                let end_time = NaiveTime::parse_from_str(end_time, &mapping.time_format)?;
+                match field_opt(self.end_date) {
+                    Some(end_date) => NaiveDate::parse_from_str(end_date, &mapping.date_format)?
+                        .and_time(end_time),
+                    // without an end date, an end before the start is on the next day
+                    None if end_time < start_datetime.time() => {
+                        (start_date + Duration::days(1)).and_time(end_time)
+                    }
+                    None => start_date.and_time(end_time),
+                }
+            }
+            (None, Some(duration)) => start_datetime + parse_duration(duration)?,
+            (None, None) => return Err(eyre!("no end time nor duration")),
+        };
+        if end_datetime < start_datetime {
+            return Err(eyre!("ends at {} before it starts", end_datetime));
+        }
+
+        let tags = field_opt(self.tags)
+            .map(|tags| tags.split(mapping.tag_separator).collect::<Vec<&str>>())
+            .unwrap_or_default();
+        let mut types = tags_to_types(tags.into_iter().filter(|tag| !tag.trim().is_empty()));
+        if let Some(project) = field_opt(self.project) {
+            types.1.insert(project.to_string());
+        }
+
+        Ok(Activity {
+            start_datetime,
+            end_datetime,
+            description: field_opt(self.description).unwrap_or("").to_string(),
+            action: types.action(),
+            projects: types.1,
+            source: mapping.source.clone(),
+            id: None,
+        })
+    }
+}
+
+// parse_duration reads 01:30:00, 01:30 or decimal hours like 1.5
+fn parse_duration(input: &str) -> Result<Duration> {
+    if input.contains(':') {
+        let mut parts = input.split(':').map(|part| part.parse::<i64>());
+        let hours = parts.next().unwrap_or(Ok(0))?;
+        let minutes = parts.next().unwrap_or(Ok(0))?;
+        let seconds = parts.next().unwrap_or(Ok(0))?;
+
+        return Ok(Duration::seconds(hours * 3600 + minutes * 60 + seconds));
+    }
+
+    let hours = input.parse::<f64>()?;
+
+    Ok(Duration::seconds((hours * 3600.0).round() as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Action;
+    use chrono::NaiveDateTime;
+
+    fn datetime(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_import_toggl() {
+        let csv = "User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags,Amount ()
+jb,jb@mail.com,,rust,,fixed loader,No,2022-05-27,09:00:00,2022-05-27,10:30:00,01:30:00,\"code, perf\",
+jb,jb@mail.com,,,,weekly,No,2022-05-27,14:00:00,2022-05-27,15:00:00,01:00:00,meeting,
+jb,jb@mail.com,,rust,,broken,No,27/05/2022,14:00:00,,,,,
+";
+        let activities = import_csv(csv.as_bytes(), &CsvMapping::toggl()).unwrap();
+
+        assert_eq!(
+            activities.0,
+            vec![
+                Activity {
+                    start_datetime: datetime(2022, 5, 27, 9, 0),
+                    end_datetime: datetime(2022, 5, 27, 10, 30),
+                    description: "fixed loader".to_string(),
+                    action: Action::Code,
+                    projects: ["rust".to_string(), "perf".to_string()].into(),
+                    source: "toggl".to_string(),
+                    id: None,
+                },
+                Activity {
+                    start_datetime: datetime(2022, 5, 27, 14, 0),
+                    end_datetime: datetime(2022, 5, 27, 15, 0),
+                    description: "weekly".to_string(),
+                    action: Action::Meeting,
+                    projects: [].into(),
+                    source: "toggl".to_string(),
+                    id: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_end_before_start() {
+        // without an end date the end rolls over to the next day, with one it is an error
+        let csv = "Start date,Start time,End date,End time,Description
+2022-05-27,23:00:00,,01:00:00,release
+2022-05-27,23:00:00,2022-05-27,01:00:00,broken
+";
+        let activities = import_csv(csv.as_bytes(), &CsvMapping::toggl()).unwrap();
+        assert_eq!(activities.0.len(), 1);
+        assert_eq!(activities.0[0].description, "release");
+        assert_eq!(activities.0[0].end_datetime, datetime(2022, 5, 28, 1, 0));
+    }
+
+    #[test]
+    fn test_import_clockify() {
+        let csv = "\"Project\",\"Client\",\"Description\",\"Task\",\"User\",\"Group\",\"Email\",\"Tags\",\"Billable\",\"Start Date\",\"Start Time\",\"End Date\",\"End Time\",\"Duration (h)\",\"Duration (decimal)\"
+\"rest-apis\",\"\",\"headers\",\"\",\"jb\",\"\",\"jb@mail.com\",\"review\",\"No\",\"05/27/2022\",\"09:30:00 AM\",\"05/27/2022\",\"10:00:00 AM\",\"00:30:00\",\"0.50\"
+";
+        let activities = import_csv(csv.as_bytes(), &CsvMapping::clockify()).unwrap();
+
+        assert_eq!(activities.0.len(), 1);
+        assert_eq!(activities.0[0].start_datetime, datetime(2022, 5, 27, 9, 30));
+        assert_eq!(activities.0[0].end_datetime, datetime(2022, 5, 27, 10, 0));
+        assert_eq!(activities.0[0].action, Action::Review);
+        assert_eq!(activities.0[0].projects, ["rest-apis".to_string()].into());
+    }
+
+    #[test]
+    fn test_import_generic_csv() {
+        let mapping = CsvMapping {
+            source: "sheet".to_string(),
+            start_date: "day".to_string(),
+            start_time: "from".to_string(),
+            end_date: None,
+            end_time: None,
+            duration: Some("hours".to_string()),
+            project: None,
+            tags: Some("labels".to_string()),
+            tag_separator: ';',
+            description: Some("what".to_string()),
+            date_format: "%d.%m.%Y".to_string(),
+            time_format: "%H:%M".to_string(),
+        };
+        let csv = "day,from,hours,labels,what\n27.05.2022,11:00,0.75,docs;rust,readme\n";
+        let activities = import_csv(csv.as_bytes(), &mapping).unwrap();
+
+        assert_eq!(activities.0.len(), 1);
+        assert_eq!(activities.0[0].end_datetime, datetime(2022, 5, 27, 11, 45));
+        assert_eq!(activities.0[0].action, Action::Docs);
+        assert_eq!(activities.0[0].projects, ["rust".to_string()].into());
+        assert_eq!(activities.0[0].description, "readme");
+
+        let csv = "day,labels\n27.05.2022,docs\n";
+        assert!(import_csv(csv.as_bytes(), &mapping).is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("01:30:00").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("0:45").unwrap(), Duration::minutes(45));
+        assert_eq!(parse_duration("1.5").unwrap(), Duration::minutes(90));
+        assert!(parse_duration("abc").is_err());
+    }
+}
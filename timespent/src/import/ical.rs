@@ -0,0 +1,790 @@
+use crate::activity::{Action, Activities, Activity};
+use chrono::{Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{prelude::*, BufReader};
+
+// recurring events without an end stop being expanded after this many occurrences
+const MAX_OCCURRENCES: usize = 10_000;
+
+// IcsOptions bounds the expansion of recurring events and tells how to find projects,
+// keyword_projects maps a lowercase word of the summary to a project
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct IcsOptions {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub categories_as_projects: bool,
+    pub keyword_projects: HashMap<String, String>,
+}
+
+// CalendarEvent is one occurrence of a calendar event, recurring events give one per occurrence
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub start_datetime: NaiveDateTime,
+    pub end_datetime: NaiveDateTime,
+    pub categories: Vec<String>,
+}
+
+pub fn import_ics_file(path: &str, options: &IcsOptions) -> Result<Activities> {
+    import_ics(BufReader::new(File::open(path)?), &Local, options)
+}
+
+// import_ics turns the timed events of a calendar into meetings
+pub fn import_ics<R: BufRead, Tz: TimeZone>(
+    reader: R,
+    tz: &Tz,
+    options: &IcsOptions,
+) -> Result<Activities> {
+    let events = read_events(reader, tz, &options.from, &options.to)?;
+
+    Ok(events
+        .into_iter()
+        .map(|event| {
+            let mut projects = HashSet::new();
+            if options.categories_as_projects {
+                projects.extend(event.categories.iter().cloned());
+            }
+
+            let summary = event.summary.to_lowercase();
+            options
+                .keyword_projects
+                .iter()
+                .filter(|(keyword, _)| {
+                    summary
+                        .split(|c: char| !c.is_alphanumeric() && c != '-')
+                        .any(|word| word == keyword.to_lowercase())
+                })
+                .for_each(|(_, project)| {
+                    projects.insert(project.clone());
+                });
+
+            Activity {
+                start_datetime: event.start_datetime,
+                end_datetime: event.end_datetime,
+                description: event.summary,
+                action: Action::Meeting,
+                projects,
+                source: "calendar".to_string(),
+                id: None,
+            }
+        })
+        .collect())
+}
+
+// read_events returns the occurrences of the timed events starting between from and to,
+// all-day and cancelled events are left out
+pub fn read_events<R: BufRead, Tz: TimeZone>(
+    reader: R,
+    tz: &Tz,
+    from: &NaiveDate,
+    to: &NaiveDate,
+) -> Result<Vec<CalendarEvent>> {
+    let lines = unfold_lines(reader)?;
+
+    let mut vevents: Vec<Vec<Property>> = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+    for line in lines {
+        match line.as_str() {
+            "BEGIN:VEVENT" => current = Some(Vec::new()),
+            "END:VEVENT" => {
+                if let Some(vevent) = current.take() {
+                    vevents.push(vevent);
+                }
+            }
+            _ => {
+                if let (Some(vevent), Some(property)) = (current.as_mut(), Property::parse(&line)) {
+                    vevent.push(property);
+                }
+            }
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut overrides: HashSet<(String, NaiveDateTime)> = HashSet::new();
+    let mut recurring = Vec::new();
+
+    for vevent in vevents {
+        let event = match VEvent::new(&vevent, tz) {
+            Ok(Some(event)) => event,
+            Ok(None) => continue,
+            Err(err) => {
+                eprintln!("can not import event: {:?} {}", vevent, err);
+                continue;
+            }
+        };
+
+        if let Some(recurrence_id) = event.recurrence_id {
+            overrides.insert((event.event.uid.clone(), recurrence_id));
+        }
+
+        if event.rrule.is_some() {
+            recurring.push(event);
+        } else {
+            events.push(event.event);
+        }
+    }
+
+    for event in recurring {
+        let duration = event.event.end_datetime - event.event.start_datetime;
+        let rrule = event.rrule.as_ref().unwrap();
+
+        match expand_rrule(&event.event.start_datetime, rrule, from, to) {
+            Ok(starts) => starts
+                .into_iter()
+                .filter(|start| !event.exdates.contains(start))
+                .filter(|start| !overrides.contains(&(event.event.uid.clone(), *start)))
+                .for_each(|start| {
+                    events.push(CalendarEvent {
+                        start_datetime: start,
+                        end_datetime: start + duration,
+                        ..event.event.clone()
+                    })
+                }),
+            Err(err) => eprintln!("can not expand {}: {}", rrule, err),
+        }
+    }
+
+    events
+        .retain(|event| event.start_datetime.date() >= *from && event.start_datetime.date() <= *to);
+    events.sort_by_key(|event| event.start_datetime);
+
+    Ok(events)
+}
+
+// long lines are folded on several lines starting with a space
+fn unfold_lines<R: BufRead>(reader: R) -> Result<Vec<String>> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        let line = line.trim_end_matches('\r');
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&line[1..]);
+                continue;
+            }
+        }
+        lines.push(line.to_string());
+    }
+
+    Ok(lines)
+}
+
+// Property is a content line: NAME;PARAM=VALUE:value
+#[derive(Debug)]
+struct Property {
+    name: String,
+    params: HashMap<String, String>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Property> {
+        let (head, value) = line.split_once(':')?;
+        let mut head = head.split(';');
+        let name = head.next()?.to_uppercase();
+        let params = head
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| (key.to_uppercase(), value.trim_matches('"').to_string()))
+            .collect();
+
+        Some(Property {
+            name,
+            params,
+            value: value.to_string(),
+        })
+    }
+}
+
+struct VEvent {
+    event: CalendarEvent,
+    rrule: Option<String>,
+    exdates: Vec<NaiveDateTime>,
+    recurrence_id: Option<NaiveDateTime>,
+}
+
+impl VEvent {
+    // new returns None for events that are not timed meetings
+    fn new<Tz: TimeZone>(properties: &[Property], tz: &Tz) -> Result<Option<VEvent>> {
+        let get = |name: &str| properties.iter().find(|property| property.name == name);
+
+        if get("STATUS").map(|status| status.value.as_str()) == Some("CANCELLED") {
+            return Ok(None);
+        }
+
+        let dtstart = get("DTSTART").ok_or_else(|| eyre!("missing DTSTART"))?;
+        let start_datetime = match parse_datetime(dtstart, tz)? {
+            Some(start_datetime) => start_datetime,
+            None => return Ok(None),
+        };
+
+        let end_datetime = match (get("DTEND"), get("DURATION")) {
+            (Some(dtend), _) => {
+                parse_datetime(dtend, tz)?.ok_or_else(|| eyre!("DTEND is a date"))?
+            }
+            (None, Some(duration)) => start_datetime + parse_duration(&duration.value)?,
+            (None, None) => start_datetime,
+        };
+
+        let mut exdates = Vec::new();
+        for exdate in properties
+            .iter()
+            .filter(|property| property.name == "EXDATE")
+        {
+            for value in exdate.value.split(',') {
+                let property = Property {
+                    name: exdate.name.clone(),
+                    params: exdate.params.clone(),
+                    value: value.to_string(),
+                };
+                if let Some(exdate) = parse_datetime(&property, tz)? {
+                    exdates.push(exdate);
+                }
+            }
+        }
+
+        let recurrence_id = match get("RECURRENCE-ID") {
+            Some(recurrence_id) => parse_datetime(recurrence_id, tz)?,
+            None => None,
+        };
+
+        Ok(Some(VEvent {
+            event: CalendarEvent {
+                uid: get("UID").map(|uid| uid.value.clone()).unwrap_or_default(),
+                summary: get("SUMMARY")
+                    .map(|summary| unescape(&summary.value))
+                    .unwrap_or_default(),
+                start_datetime,
+                end_datetime,
+                categories: properties
+                    .iter()
+                    .filter(|property| property.name == "CATEGORIES")
+                    .flat_map(|property| property.value.split(','))
+                    .map(|category| unescape(category.trim()))
+                    .filter(|category| !category.is_empty())
+                    .collect(),
+            },
+            rrule: get("RRULE").map(|rrule| rrule.value.clone()),
+            exdates,
+            recurrence_id,
+        }))
+    }
+}
+
+// parse_datetime returns None for dates, utc times are turned into tz times,
+// times with a TZID or floating times are kept as they are written
+fn parse_datetime<Tz: TimeZone>(property: &Property, tz: &Tz) -> Result<Option<NaiveDateTime>> {
+    let value = property.value.trim();
+
+    if property.params.get("VALUE").map(|v| v.as_str()) == Some("DATE") || value.len() == 8 {
+        return Ok(None);
+    }
+
+    if let Some(utc) = value.strip_suffix('Z') {
+        let utc = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S")?;
+        return Ok(Some(
+            Utc.from_utc_datetime(&utc).with_timezone(tz).naive_local(),
+        ));
+    }
+
+    Ok(Some(NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S")?))
+}
+
+// parse_duration reads durations like PT1H30M or P1DT2H
+fn parse_duration(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    let (negative, input) = match input.strip_prefix('-') {
+        Some(input) => (true, input),
+        None => (false, input.trim_start_matches('+')),
+    };
+    let input = input
+        .strip_prefix('P')
+        .ok_or_else(|| eyre!("invalid duration {}", input))?;
+
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+    for c in input.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => continue,
+            unit => {
+                let value = number.parse::<i64>()?;
+                number.clear();
+                duration += match unit {
+                    'W' => Duration::weeks(value),
+                    'D' => Duration::days(value),
+                    'H' => Duration::hours(value),
+                    'M' => Duration::minutes(value),
+                    'S' => Duration::seconds(value),
+                    _ => return Err(eyre!("invalid duration unit {}", unit)),
+                };
+            }
+        }
+    }
+
+    Ok(if negative { -duration } else { duration })
+}
+
+fn unescape(input: &str) -> String {
+    input
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+// expand_rrule returns the starts of a recurring event between from and to,
+// it handles FREQ, INTERVAL, COUNT, UNTIL, BYMONTH for yearly rules and BYDAY,
+// with ordinals like 2TU or -1FR for monthly and yearly rules
+fn expand_rrule(
+    dtstart: &NaiveDateTime,
+    rrule: &str,
+    from: &NaiveDate,
+    to: &NaiveDate,
+) -> Result<Vec<NaiveDateTime>> {
+    let parts: HashMap<String, String> = rrule
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, value)| (key.to_uppercase(), value.to_string()))
+        .collect();
+
+    let freq = parts
+        .get("FREQ")
+        .ok_or_else(|| eyre!("missing FREQ"))?
+        .as_str();
+    let interval = match parts.get("INTERVAL") {
+        Some(interval) => interval.parse::<u32>()?.max(1),
+        None => 1,
+    };
+    let count = match parts.get("COUNT") {
+        Some(count) => Some(count.parse::<usize>()?),
+        None => None,
+    };
+    let until = match parts.get("UNTIL") {
+        Some(until) => Some(parse_until(until)?),
+        None => None,
+    };
+    let by_day = match parts.get("BYDAY") {
+        Some(by_day) => by_day
+            .split(',')
+            .map(parse_by_day)
+            .collect::<Result<Vec<(Option<i64>, Weekday)>>>()?,
+        None => Vec::new(),
+    };
+    let by_month = match parts.get("BYMONTH") {
+        Some(by_month) => by_month
+            .split(',')
+            .map(|month| month.trim().parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>()?,
+        None => Vec::new(),
+    };
+    if (freq == "DAILY" || freq == "WEEKLY") && by_day.iter().any(|(nth, _)| nth.is_some()) {
+        return Err(eyre!("BYDAY ordinals need a MONTHLY or YEARLY rule"));
+    }
+
+    let range_start = from.and_hms_opt(0, 0, 0).unwrap();
+    let limit = to.and_hms_opt(23, 59, 59).unwrap();
+    let mut seen = 0;
+    let mut starts = Vec::new();
+
+    // occurrences before the range are only walked through to count them
+    for period in 0.. {
+        let step = period * interval;
+        let (period_start, mut candidates) = match freq {
+            "DAILY" => {
+                let day = dtstart.date() + Duration::days(step as i64);
+                (day, vec![day])
+            }
+            "WEEKLY" => {
+                let week_start = dtstart.date() + Duration::weeks(step as i64)
+                    - Duration::days(dtstart.weekday().num_days_from_monday() as i64);
+                let days = if by_day.is_empty() {
+                    vec![dtstart.date() + Duration::weeks(step as i64)]
+                } else {
+                    by_day
+                        .iter()
+                        .map(|(_, day)| {
+                            week_start + Duration::days(day.num_days_from_monday() as i64)
+                        })
+                        .collect()
+                };
+                (week_start, days)
+            }
+            "MONTHLY" => {
+                let first = dtstart
+                    .date()
+                    .with_day(1)
+                    .and_then(|first| first.checked_add_months(Months::new(step)))
+                    .ok_or_else(|| eyre!("{} goes past the last date", rrule))?;
+                let days = if by_day.is_empty() {
+                    first.with_day(dtstart.day()).into_iter().collect()
+                } else {
+                    month_days(first, &by_day)
+                };
+                (first, days)
+            }
+            "YEARLY" => {
+                let year = dtstart.year() + step as i32;
+                let (first, last) = NaiveDate::from_ymd_opt(year, 1, 1)
+                    .zip(NaiveDate::from_ymd_opt(year, 12, 31))
+                    .ok_or_else(|| eyre!("{} goes past the last date", rrule))?;
+                let days = if by_day.is_empty() {
+                    NaiveDate::from_ymd_opt(year, dtstart.month(), dtstart.day())
+                        .into_iter()
+                        .collect()
+                } else if by_month.is_empty() {
+                    weekdays_between(first, last, &by_day)
+                } else {
+                    by_month
+                        .iter()
+                        .filter_map(|month| first.with_month(*month))
+                        .flat_map(|first| month_days(first, &by_day))
+                        .collect()
+                };
+                (first, days)
+            }
+            _ => return Err(eyre!("unsupported FREQ {}", freq)),
+        };
+
+        if period_start > *to {
+            return Ok(starts);
+        }
+
+        candidates.sort();
+        candidates.dedup();
+        for start in candidates
+            .into_iter()
+            .map(|day| day.and_time(dtstart.time()))
+            .filter(|start| start >= dtstart)
+        {
+            if start > limit || until.map(|until| start > until) == Some(true) {
+                return Ok(starts);
+            }
+            seen += 1;
+            if start >= range_start {
+                starts.push(start);
+            }
+            if count.map(|count| seen >= count) == Some(true) || starts.len() >= MAX_OCCURRENCES {
+                return Ok(starts);
+            }
+        }
+    }
+
+    Ok(starts)
+}
+
+// month_days returns the days matching BYDAY in the month starting at first
+fn month_days(first: NaiveDate, by_day: &[(Option<i64>, Weekday)]) -> Vec<NaiveDate> {
+    let last = first
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .unwrap_or(first);
+
+    weekdays_between(first, last, by_day)
+}
+
+// weekdays_between returns the days from first to last matching BYDAY,
+// 2TU is the second tuesday and -1FR the last friday of the period
+fn weekdays_between(
+    first: NaiveDate,
+    last: NaiveDate,
+    by_day: &[(Option<i64>, Weekday)],
+) -> Vec<NaiveDate> {
+    by_day
+        .iter()
+        .flat_map(|(nth, weekday)| {
+            let days = first
+                .iter_days()
+                .take_while(|day| *day <= last)
+                .filter(|day| day.weekday() == *weekday)
+                .collect::<Vec<NaiveDate>>();
+            match nth {
+                None => days,
+                Some(nth) if *nth > 0 => days.get(*nth as usize - 1).copied().into_iter().collect(),
+                Some(nth) => days
+                    .len()
+                    .checked_sub(nth.unsigned_abs() as usize)
+                    .and_then(|index| days.get(index))
+                    .copied()
+                    .into_iter()
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+fn parse_until(input: &str) -> Result<NaiveDateTime> {
+    let input = input.trim_end_matches('Z');
+    if input.len() == 8 {
+        return Ok(NaiveDate::parse_from_str(input, "%Y%m%d")?
+            .and_hms_opt(23, 59, 59)
+            .unwrap());
+    }
+
+    Ok(NaiveDateTime::parse_from_str(input, "%Y%m%dT%H%M%S")?)
+}
+
+// parse_by_day reads a BYDAY value like MO, 2TU or -1FR
+fn parse_by_day(input: &str) -> Result<(Option<i64>, Weekday)> {
+    let input = input.trim();
+    let split = input
+        .len()
+        .checked_sub(2)
+        .filter(|split| input.is_char_boundary(*split))
+        .ok_or_else(|| eyre!("unsupported BYDAY {}", input))?;
+    let (nth, day) = input.split_at(split);
+    let nth = match nth {
+        "" => None,
+        nth => match nth.trim_start_matches('+').parse::<i64>() {
+            Ok(nth) if nth != 0 && nth.abs() <= 53 => Some(nth),
+            _ => return Err(eyre!("unsupported BYDAY {}", input)),
+        },
+    };
+
+    Ok((
+        nth,
+        parse_weekday(day).map_err(|_| eyre!("unsupported BYDAY {}", input))?,
+    ))
+}
+
+fn parse_weekday(input: &str) -> Result<Weekday> {
+    match input.trim() {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        day => Err(eyre!("unsupported BYDAY {}", day)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn datetime(m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2022, m, d)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r
+VERSION:2.0\r
+BEGIN:VEVENT\r
+UID:weekly@timespent\r
+SUMMARY:Weekly timespent\r
+ \\, planning\r
+CATEGORIES:management\r
+DTSTART;TZID=Europe/Paris:20220523T100000\r
+DTEND;TZID=Europe/Paris:20220523T103000\r
+RRULE:FREQ=WEEKLY;BYDAY=MO,WE;COUNT=5\r
+EXDATE;TZID=Europe/Paris:20220525T100000\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:weekly@timespent\r
+RECURRENCE-ID;TZID=Europe/Paris:20220530T100000\r
+SUMMARY:Weekly timespent moved\r
+DTSTART;TZID=Europe/Paris:20220530T140000\r
+DURATION:PT1H\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:review@timespent\r
+SUMMARY:Review rust loader\r
+DTSTART:20220527T070000Z\r
+DTEND:20220527T080000Z\r
+END:VEVENT\r
+BEGIN:VEVENT\r
+UID:holiday@timespent\r
+SUMMARY:Holiday\r
+DTSTART;VALUE=DATE:20220526\r
+END:VEVENT\r
+END:VCALENDAR\r
+";
+
+    #[test]
+    fn test_read_events() {
+        let events = read_events(
+            CALENDAR.as_bytes(),
+            &Utc,
+            &NaiveDate::from_ymd_opt(2022, 5, 1).unwrap(),
+            &NaiveDate::from_ymd_opt(2022, 6, 30).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.start_datetime, event.end_datetime))
+                .collect::<Vec<(NaiveDateTime, NaiveDateTime)>>(),
+            vec![
+                (datetime(5, 23, 10, 0), datetime(5, 23, 10, 30)),
+                (datetime(5, 27, 7, 0), datetime(5, 27, 8, 0)),
+                (datetime(5, 30, 14, 0), datetime(5, 30, 15, 0)),
+                (datetime(6, 1, 10, 0), datetime(6, 1, 10, 30)),
+                (datetime(6, 6, 10, 0), datetime(6, 6, 10, 30)),
+            ]
+        );
+        assert_eq!(events[0].summary, "Weekly timespent, planning");
+        assert_eq!(events[0].categories, vec!["management".to_string()]);
+    }
+
+    #[test]
+    fn test_import_ics() {
+        let options = IcsOptions {
+            from: NaiveDate::from_ymd_opt(2022, 5, 26).unwrap(),
+            to: NaiveDate::from_ymd_opt(2022, 5, 31).unwrap(),
+            categories_as_projects: true,
+            keyword_projects: [("rust".to_string(), "rust".to_string())].into(),
+        };
+        let activities = import_ics(CALENDAR.as_bytes(), &Utc, &options).unwrap();
+
+        assert_eq!(
+            activities.0,
+            vec![
+                Activity {
+                    start_datetime: datetime(5, 27, 7, 0),
+                    end_datetime: datetime(5, 27, 8, 0),
+                    description: "Review rust loader".to_string(),
+                    action: Action::Meeting,
+                    projects: ["rust".to_string()].into(),
+                    source: "calendar".to_string(),
+                    id: None,
+                },
+                Activity {
+                    start_datetime: datetime(5, 30, 14, 0),
+                    end_datetime: datetime(5, 30, 15, 0),
+                    description: "Weekly timespent moved".to_string(),
+                    action: Action::Meeting,
+                    projects: [].into(),
+                    source: "calendar".to_string(),
+                    id: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_rrule() {
+        let from = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2022, 12, 31).unwrap();
+
+        assert_eq!(
+            expand_rrule(&datetime(1, 31, 9, 0), "FREQ=MONTHLY;COUNT=3", &from, &to).unwrap(),
+            vec![
+                datetime(1, 31, 9, 0),
+                datetime(3, 31, 9, 0),
+                datetime(5, 31, 9, 0)
+            ]
+        );
+        assert_eq!(
+            expand_rrule(
+                &datetime(5, 27, 9, 0),
+                "FREQ=DAILY;INTERVAL=2;UNTIL=20220601",
+                &from,
+                &to
+            )
+            .unwrap(),
+            vec![
+                datetime(5, 27, 9, 0),
+                datetime(5, 29, 9, 0),
+                datetime(5, 31, 9, 0)
+            ]
+        );
+        assert_eq!(
+            expand_rrule(&datetime(12, 26, 9, 0), "FREQ=WEEKLY", &from, &to)
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(expand_rrule(&datetime(5, 27, 9, 0), "FREQ=HOURLY", &from, &to).is_err());
+        assert!(expand_rrule(&datetime(5, 27, 9, 0), "FREQ=WEEKLY;BYDAY=2TU", &from, &to).is_err());
+    }
+
+    #[test]
+    fn test_expand_rrule_by_day_ordinals() {
+        let from = NaiveDate::from_ymd_opt(2022, 1, 1).unwrap();
+        let to = NaiveDate::from_ymd_opt(2022, 12, 31).unwrap();
+
+        assert_eq!(
+            expand_rrule(
+                &datetime(5, 10, 9, 0),
+                "FREQ=MONTHLY;BYDAY=2TU;COUNT=3",
+                &from,
+                &to
+            )
+            .unwrap(),
+            vec![
+                datetime(5, 10, 9, 0),
+                datetime(6, 14, 9, 0),
+                datetime(7, 12, 9, 0)
+            ]
+        );
+        assert_eq!(
+            expand_rrule(
+                &datetime(5, 27, 16, 0),
+                "FREQ=MONTHLY;BYDAY=-1FR;UNTIL=20220801",
+                &from,
+                &to
+            )
+            .unwrap(),
+            vec![
+                datetime(5, 27, 16, 0),
+                datetime(6, 24, 16, 0),
+                datetime(7, 29, 16, 0)
+            ]
+        );
+        assert_eq!(
+            expand_rrule(
+                &datetime(1, 1, 12, 0),
+                "FREQ=YEARLY;BYMONTH=11;BYDAY=4TH",
+                &from,
+                &to
+            )
+            .unwrap(),
+            vec![datetime(11, 24, 12, 0)]
+        );
+    }
+
+    #[test]
+    fn test_expand_rrule_old_series() {
+        let dtstart = NaiveDate::from_ymd_opt(1990, 1, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let from = NaiveDate::from_ymd_opt(2022, 5, 23).unwrap();
+        let to = NaiveDate::from_ymd_opt(2022, 5, 24).unwrap();
+
+        assert_eq!(
+            expand_rrule(&dtstart, "FREQ=DAILY", &from, &to).unwrap(),
+            vec![datetime(5, 23, 9, 0), datetime(5, 24, 9, 0)]
+        );
+        assert!(expand_rrule(&dtstart, "FREQ=DAILY;COUNT=10", &from, &to)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_by_day() {
+        assert_eq!(parse_by_day("MO").unwrap(), (None, Weekday::Mon));
+        assert_eq!(parse_by_day("2TU").unwrap(), (Some(2), Weekday::Tue));
+        assert_eq!(parse_by_day("-1FR").unwrap(), (Some(-1), Weekday::Fri));
+        assert!(parse_by_day("0MO").is_err());
+        assert!(parse_by_day("1É").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("PT1H30M").unwrap(), Duration::minutes(90));
+        assert_eq!(parse_duration("P1DT2H").unwrap(), Duration::hours(26));
+        assert_eq!(parse_duration("-PT15M").unwrap(), Duration::minutes(-15));
+        assert!(parse_duration("1H").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+pub mod csv;
+pub mod ical;
+pub mod org;
+pub mod timewarrior;
+pub mod watson;
+
+use crate::activity::{Activities, Activity};
+use crate::loader::Source;
+use crate::writer::{contains_activity, insert_activity, split_at_midnight};
+use eyre::Result;
+use std::fmt::Display;
+
+// ImportSummary tells what write_day_files did with the imported entries, day files
+// can not hold everything an import gives
+#[derive(Eq, PartialEq, Debug, Clone, Default)]
+pub struct ImportSummary {
+    pub written: usize,
+    // already in the day files
+    pub skipped: usize,
+    // under a minute, day files have a minute precision
+    pub dropped: usize,
+    // a day file ends at 23h59, an entry crossing midnight loses a minute per midnight
+    pub truncated_minutes: i64,
+}
+
+impl Display for ImportSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} written, {} already there, {} under a minute dropped, {} minutes lost at midnight (day files end at 23h59)",
+            self.written, self.skipped, self.dropped, self.truncated_minutes
+        )
+    }
+}
+
+// write_day_files adds imported activities to the YYYY.MM.DD.txt day files of the source,
+// entries crossing midnight are split per day and the ones already written are skipped,
+// so importing the same file twice adds nothing
+pub fn write_day_files(source: &Source, activities: &Activities) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+
+    for activity in &activities.0 {
+        let pieces = split_at_midnight(activity);
+        if pieces.is_empty() {
+            eprintln!("entry under a minute dropped: {}", activity);
+            summary.dropped += 1;
+            continue;
+        }
+        summary.truncated_minutes += minutes(activity) - pieces.iter().map(minutes).sum::<i64>();
+
+        for piece in pieces {
+            if contains_activity(&source.path, &piece)? {
+                summary.skipped += 1;
+            } else {
+                insert_activity(source, &piece)?;
+                summary.written += 1;
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+fn minutes(activity: &Activity) -> i64 {
+    (activity.end_datetime - activity.start_datetime).num_minutes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Action;
+    use crate::test_utils::{activity, test_dir};
+    use chrono::NaiveDate;
+    use std::fs::read_to_string;
+    use std::path::Path;
+
+    #[test]
+    fn test_write_day_files() {
+        let dir = test_dir("import-write");
+        let base_path = dir.path();
+        let source = Source {
+            name: "work".to_string(),
+            path: base_path.to_string(),
+        };
+
+        let activities = Activities(vec![Activity {
+            start_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                .unwrap()
+                .and_hms_opt(22, 0, 0)
+                .unwrap(),
+            end_datetime: NaiveDate::from_ymd_opt(2022, 5, 28)
+                .unwrap()
+                .and_hms_opt(1, 0, 0)
+                .unwrap(),
+            description: "release".to_string(),
+            action: Action::Code,
+            projects: ["rust".to_string()].into(),
+            source: String::new(),
+            id: None,
+        }]);
+
+        let summary = write_day_files(&source, &activities).unwrap();
+        assert_eq!(
+            summary,
+            ImportSummary {
+                written: 2,
+                skipped: 0,
+                dropped: 0,
+                truncated_minutes: 1,
+            }
+        );
+        let summary = write_day_files(&source, &activities).unwrap();
+        assert_eq!((summary.written, summary.skipped), (0, 2));
+
+        // an entry without any time is reported, not written
+        let empty = Activities(vec![activity(
+            26,
+            (9, 0),
+            (9, 0),
+            Action::Code,
+            &["rust"],
+            "",
+        )]);
+        assert_eq!(write_day_files(&source, &empty).unwrap().dropped, 1);
+        assert!(!Path::new(base_path).join("2022.05.26.txt").exists());
+
+        assert_eq!(
+            read_to_string(Path::new(base_path).join("2022.05.27.txt")).unwrap(),
+            "22h00-23h59: [code][rust] release\n"
+        );
+        assert_eq!(
+            read_to_string(Path::new(base_path).join("2022.05.28.txt")).unwrap(),
+            "00h00-01h00: [code][rust] release\n"
+        );
+    }
+}
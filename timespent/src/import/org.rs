@@ -0,0 +1,197 @@
+use crate::activity::{Activities, Activity};
+use crate::parser::tags_to_types;
+use chrono::NaiveDateTime;
+use eyre::{eyre, Result};
+use std::fs::File;
+use std::io::{prelude::*, BufReader};
+
+const TODO_KEYWORDS: [&str; 6] = ["TODO", "NEXT", "WAITING", "DONE", "CANCELLED", "CANCELED"];
+
+pub fn import_org_file(path: &str) -> Result<Activities> {
+    import_org(BufReader::new(File::open(path)?))
+}
+
+// import_org turns the CLOCK entries of an org file into activities, the heading above
+// gives the description, its tags and the tags of its parents give the action and projects
+pub fn import_org<R: BufRead>(reader: R) -> Result<Activities> {
+    let mut activities = Vec::new();
+    // (level, title, tags) of the headings leading to the current one
+    let mut headings: Vec<(usize, String, Vec<String>)> = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+
+        if let Some((level, title, tags)) = parse_heading(&line) {
+            headings.retain(|(heading_level, _, _)| *heading_level < level);
+            headings.push((level, title, tags));
+            continue;
+        }
+
+        let clock = match line.trim().strip_prefix("CLOCK:") {
+            Some(clock) => clock,
+            None => continue,
+        };
+
+        let (start_datetime, end_datetime) = match parse_clock(clock) {
+            Ok(times) => times,
+            Err(err) => {
+                eprintln!("can not import clock: {} {}", line, err);
+                continue;
+            }
+        };
+
+        let types = tags_to_types(
+            headings
+                .iter()
+                .flat_map(|(_, _, tags)| tags.iter().map(|tag| tag.as_str())),
+        );
+
+        activities.push(Activity {
+            start_datetime,
+            end_datetime,
+            description: headings
+                .last()
+                .map(|(_, title, _)| title.clone())
+                .unwrap_or_default(),
+            action: types.action(),
+            projects: types.1,
+            source: "org".to_string(),
+            id: None,
+        });
+    }
+
+    Ok(activities.into())
+}
+
+// parse_heading turns ** TODO Fix loader   :code:rust: into (2, "Fix loader", [code, rust])
+fn parse_heading(line: &str) -> Option<(usize, String, Vec<String>)> {
+    let level = line.chars().take_while(|c| *c == '*').count();
+    if level == 0 || !line[level..].starts_with(' ') {
+        return None;
+    }
+
+    let mut title = line[level..].trim();
+    let mut tags = Vec::new();
+
+    if let Some(idx) = title.rfind(char::is_whitespace) {
+        let last_word = &title[idx + 1..];
+        if last_word.len() > 1 && last_word.starts_with(':') && last_word.ends_with(':') {
+            tags = last_word
+                .split(':')
+                .filter(|tag| !tag.is_empty())
+                .map(|tag| tag.to_string())
+                .collect();
+            title = title[..idx].trim_end();
+        }
+    }
+
+    if let Some((keyword, rest)) = title.split_once(' ') {
+        if TODO_KEYWORDS.contains(&keyword) {
+            title = rest.trim_start();
+        }
+    }
+
+    Some((level, title.to_string(), tags))
+}
+
+// parse_clock turns [2022-05-27 Fri 09:00]--[2022-05-27 Fri 09:30] =>  0:30 into start and end
+fn parse_clock(input: &str) -> Result<(NaiveDateTime, NaiveDateTime)> {
+    let (start, rest) = input
+        .split_once("--")
+        .ok_or_else(|| eyre!("clock still running"))?;
+    let end = rest.split("=>").next().unwrap_or("");
+
+    Ok((parse_org_timestamp(start)?, parse_org_timestamp(end)?))
+}
+
+// the day name between the date and the time is ignored
+fn parse_org_timestamp(input: &str) -> Result<NaiveDateTime> {
+    let input = input.trim().trim_start_matches('[').trim_end_matches(']');
+    let parts = input.split_whitespace().collect::<Vec<&str>>();
+    let (date, time) = match parts.as_slice() {
+        [date, _, time] => (date, time),
+        [date, time] => (date, time),
+        _ => return Err(eyre!("invalid timestamp {}", input)),
+    };
+
+    Ok(NaiveDateTime::parse_from_str(
+        &format!("{} {}", date, time),
+        "%Y-%m-%d %H:%M",
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Action;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_parse_heading() {
+        assert_eq!(
+            parse_heading("** TODO Fix loader   :code:rust:"),
+            Some((
+                2,
+                "Fix loader".to_string(),
+                vec!["code".to_string(), "rust".to_string()]
+            ))
+        );
+        assert_eq!(
+            parse_heading("* Weekly"),
+            Some((1, "Weekly".to_string(), vec![]))
+        );
+        assert_eq!(parse_heading("*bold* text"), None);
+        assert_eq!(parse_heading("CLOCK: [2022-05-27 Fri 09:00]"), None);
+    }
+
+    #[test]
+    fn test_import_org() {
+        let org = "#+TITLE: work
+* Timespent :rust:
+** DONE Fix loader :code:
+   :LOGBOOK:
+   CLOCK: [2022-05-27 Fri 09:00]--[2022-05-27 Fri 09:30] =>  0:30
+   CLOCK: [2022-05-27 Fri 14:00]
+   :END:
+* Weekly :meeting:management:
+  CLOCK: [2022-05-27 Fri 17:30]--[2022-05-27 Fri 18:00] =>  0:30
+";
+        let activities = import_org(org.as_bytes()).unwrap();
+
+        assert_eq!(
+            activities.0,
+            vec![
+                Activity {
+                    start_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                        .unwrap()
+                        .and_hms_opt(9, 0, 0)
+                        .unwrap(),
+                    end_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                        .unwrap()
+                        .and_hms_opt(9, 30, 0)
+                        .unwrap(),
+                    description: "Fix loader".to_string(),
+                    action: Action::Code,
+                    projects: ["rust".to_string()].into(),
+                    source: "org".to_string(),
+                    id: None,
+                },
+                Activity {
+                    start_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                        .unwrap()
+                        .and_hms_opt(17, 30, 0)
+                        .unwrap(),
+                    end_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                        .unwrap()
+                        .and_hms_opt(18, 0, 0)
+                        .unwrap(),
+                    description: "Weekly".to_string(),
+                    action: Action::Meeting,
+                    projects: ["management".to_string()].into(),
+                    source: "org".to_string(),
+                    id: None,
+                },
+            ]
+        );
+    }
+}
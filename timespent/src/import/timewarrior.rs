@@ -0,0 +1,212 @@
+use crate::activity::{Activities, Activity};
+use crate::parser::tags_to_types;
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use eyre::{eyre, Result};
+use std::fs::{read_dir, File};
+use std::io::{prelude::*, BufReader};
+
+// import_timewarrior_dir reads the YYYY-MM.data interval files of a timewarrior data folder,
+// other files like tags.data or undo.data are not intervals. timewarrior stores utc times,
+// they are turned into local times
+pub fn import_timewarrior_dir(path: &str) -> Result<Activities> {
+    let mut paths = Vec::new();
+    for f in read_dir(path)? {
+        let path = f?.path();
+        let is_month = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".data"))
+            .is_some_and(|month| {
+                month.len() == 7
+                    && NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").is_ok()
+            });
+
+        if is_month {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    let mut activities = Vec::new();
+    for path in paths {
+        let reader = BufReader::new(File::open(&path)?);
+        activities.extend(import_timewarrior(reader, &Local)?.0);
+    }
+
+    Ok(activities.into())
+}
+
+pub fn import_timewarrior<R: BufRead, Tz: TimeZone>(reader: R, tz: &Tz) -> Result<Activities> {
+    let mut activities = Vec::new();
+
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match parse_interval(&line, tz) {
+            Ok(activity) => activities.push(activity),
+            Err(err) => eprintln!("can not import interval: {} {}", line, err),
+        }
+    }
+
+    Ok(activities.into())
+}
+
+// parse_interval turns inc 20220527T070000Z - 20220527T083000Z # code rust # "annotation" into an activity
+fn parse_interval<Tz: TimeZone>(line: &str, tz: &Tz) -> Result<Activity> {
+    let mut sections = line.splitn(3, " # ");
+    let times = sections.next().unwrap_or("");
+    let tags = split_tags(sections.next().unwrap_or(""));
+    let annotation = sections.next().unwrap_or("").trim().trim_matches('"');
+
+    let times = times
+        .strip_prefix("inc ")
+        .ok_or_else(|| eyre!("not an interval"))?;
+    let (start, end) = times
+        .split_once(" - ")
+        .ok_or_else(|| eyre!("interval still open"))?;
+
+    let types = tags_to_types(tags.iter().map(|tag| tag.as_str()));
+
+    Ok(Activity {
+        start_datetime: parse_utc_datetime(start, tz)?,
+        end_datetime: parse_utc_datetime(end, tz)?,
+        description: annotation.to_string(),
+        action: types.action(),
+        projects: types.1,
+        source: "timewarrior".to_string(),
+        id: None,
+    })
+}
+
+fn parse_utc_datetime<Tz: TimeZone>(input: &str, tz: &Tz) -> Result<NaiveDateTime> {
+    let utc = NaiveDateTime::parse_from_str(input.trim(), "%Y%m%dT%H%M%SZ")?;
+
+    Ok(Utc.from_utc_datetime(&utc).with_timezone(tz).naive_local())
+}
+
+// tags are separated by spaces, a quoted tag can contain spaces
+fn split_tags(input: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut tag = String::new();
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ' ' if !quoted => {
+                if !tag.is_empty() {
+                    tags.push(tag.clone());
+                    tag.clear();
+                }
+            }
+            _ => tag.push(c),
+        }
+    }
+    if !tag.is_empty() {
+        tags.push(tag);
+    }
+
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Action;
+    use crate::test_utils::test_dir;
+    use chrono::FixedOffset;
+    use std::fs::write;
+    use std::path::Path;
+
+    #[test]
+    fn test_import_timewarrior() {
+        let data = "inc 20220527T070000Z - 20220527T083000Z # code rust \"rest apis\"
+inc 20220527T090000Z - 20220527T093000Z # meeting # \"weekly with jb\"
+
+inc 20220527T100000Z # code
+";
+        let activities = import_timewarrior(data.as_bytes(), &Utc).unwrap();
+
+        assert_eq!(
+            activities.0,
+            vec![
+                Activity {
+                    start_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                        .unwrap()
+                        .and_hms_opt(7, 0, 0)
+                        .unwrap(),
+                    end_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                        .unwrap()
+                        .and_hms_opt(8, 30, 0)
+                        .unwrap(),
+                    description: "".to_string(),
+                    action: Action::Code,
+                    projects: ["rust".to_string(), "rest apis".to_string()].into(),
+                    source: "timewarrior".to_string(),
+                    id: None,
+                },
+                Activity {
+                    start_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                        .unwrap()
+                        .and_hms_opt(9, 0, 0)
+                        .unwrap(),
+                    end_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                        .unwrap()
+                        .and_hms_opt(9, 30, 0)
+                        .unwrap(),
+                    description: "weekly with jb".to_string(),
+                    action: Action::Meeting,
+                    projects: [].into(),
+                    source: "timewarrior".to_string(),
+                    id: None,
+                },
+            ]
+        );
+
+        let activities =
+            import_timewarrior(data.as_bytes(), &FixedOffset::east_opt(7200).unwrap()).unwrap();
+        assert_eq!(
+            activities.0[0].start_datetime,
+            NaiveDate::from_ymd_opt(2022, 5, 27)
+                .unwrap()
+                .and_hms_opt(9, 0, 0)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_import_timewarrior_dir() {
+        let tmp = test_dir("timewarrior-dir");
+        let dir = Path::new(tmp.path());
+
+        write(
+            dir.join("2022-05.data"),
+            "inc 20220527T070000Z - 20220527T083000Z # code rust\n",
+        )
+        .unwrap();
+        write(
+            dir.join("2022-06.data"),
+            "inc 20220601T070000Z - 20220601T083000Z # docs\n",
+        )
+        .unwrap();
+        write(dir.join("tags.data"), "{\"code\":{\"count\":2}}\n").unwrap();
+        write(
+            dir.join("undo.data"),
+            "txn:\n  type: interval\n  before: \n  after: inc 20220527T070000Z\n",
+        )
+        .unwrap();
+
+        let activities = import_timewarrior_dir(dir.to_str().unwrap()).unwrap();
+        assert_eq!(
+            activities
+                .0
+                .iter()
+                .map(|activity| activity.action)
+                .collect::<Vec<Action>>(),
+            vec![Action::Code, Action::Docs]
+        );
+    }
+}
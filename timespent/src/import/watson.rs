@@ -0,0 +1,122 @@
+use crate::activity::{Activities, Activity};
+use crate::parser::tags_to_types;
+use chrono::{Local, NaiveDateTime, TimeZone};
+use eyre::{eyre, Result};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{BufReader, Read};
+
+// import_watson_file reads watson's frames file, its unix timestamps are turned into local times
+pub fn import_watson_file(path: &str) -> Result<Activities> {
+    import_watson(BufReader::new(File::open(path)?), &Local)
+}
+
+// a frame is [start, stop, project, id, [tags], updated_at]
+pub fn import_watson<R: Read, Tz: TimeZone>(reader: R, tz: &Tz) -> Result<Activities> {
+    let frames: Vec<Vec<Value>> = serde_json::from_reader(reader)?;
+    let mut activities = Vec::new();
+
+    for frame in frames {
+        match parse_frame(&frame, tz) {
+            Ok(activity) => activities.push(activity),
+            Err(err) => eprintln!("can not import frame: {:?} {}", frame, err),
+        }
+    }
+
+    Ok(activities.into())
+}
+
+fn parse_frame<Tz: TimeZone>(frame: &[Value], tz: &Tz) -> Result<Activity> {
+    let timestamp = |idx: usize| -> Result<NaiveDateTime> {
+        let seconds = frame
+            .get(idx)
+            .and_then(|value| value.as_i64())
+            .ok_or_else(|| eyre!("missing timestamp"))?;
+
+        Ok(tz
+            .timestamp_opt(seconds, 0)
+            .single()
+            .ok_or_else(|| eyre!("invalid timestamp {}", seconds))?
+            .naive_local())
+    };
+
+    let project = frame
+        .get(2)
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| eyre!("missing project"))?;
+    let tags = frame
+        .get(4)
+        .and_then(|value| value.as_array())
+        .map(|tags| {
+            tags.iter()
+                .filter_map(|tag| tag.as_str())
+                .collect::<Vec<&str>>()
+        })
+        .unwrap_or_default();
+
+    // the project goes through the same rules as tags, watson users often name projects after actions
+    let types = tags_to_types([project].into_iter().chain(tags));
+
+    Ok(Activity {
+        start_datetime: timestamp(0)?,
+        end_datetime: timestamp(1)?,
+        description: "".to_string(),
+        action: types.action(),
+        projects: types.1,
+        source: "watson".to_string(),
+        id: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activity::Action;
+    use chrono::{NaiveDate, Utc};
+
+    #[test]
+    fn test_import_watson() {
+        let frames = r#"[
+            [1653634800, 1653640200, "rust", "a1b2", ["code", "loader"], 1653640200],
+            [1653645600, 1653647400, "meeting", "c3d4", [], 1653647400],
+            [1653645600, "broken", "rust", "e5f6", [], 1653647400]
+        ]"#;
+        let activities = import_watson(frames.as_bytes(), &Utc).unwrap();
+
+        assert_eq!(
+            activities.0,
+            vec![
+                Activity {
+                    start_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                        .unwrap()
+                        .and_hms_opt(7, 0, 0)
+                        .unwrap(),
+                    end_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                        .unwrap()
+                        .and_hms_opt(8, 30, 0)
+                        .unwrap(),
+                    description: "".to_string(),
+                    action: Action::Code,
+                    projects: ["rust".to_string(), "loader".to_string()].into(),
+                    source: "watson".to_string(),
+                    id: None,
+                },
+                Activity {
+                    start_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                        .unwrap()
+                        .and_hms_opt(10, 0, 0)
+                        .unwrap(),
+                    end_datetime: NaiveDate::from_ymd_opt(2022, 5, 27)
+                        .unwrap()
+                        .and_hms_opt(10, 30, 0)
+                        .unwrap(),
+                    description: "".to_string(),
+                    action: Action::Meeting,
+                    projects: [].into(),
+                    source: "watson".to_string(),
+                    id: None,
+                },
+            ]
+        );
+    }
+}
@@ -6,6 +6,15 @@
 )]
 
 pub mod activity;
+pub mod analysis;
+pub mod export;
 pub mod graph;
+pub mod import;
+pub mod lint;
 pub mod loader;
 pub mod parser;
+pub mod shorthand;
+#[cfg(test)]
+mod test_utils;
+pub mod timer;
+pub mod writer;
@@ -0,0 +1,194 @@
+use crate::activity::Activity;
+use crate::parser::{parse_activity, parse_tags};
+use crate::writer::write_atomic;
+use chrono::NaiveDate;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::{read_dir, read_to_string};
+use std::path::{Path, PathBuf};
+
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct LintIssue {
+    pub path: String,
+    pub line: usize,
+    pub message: String,
+}
+
+// lint_day checks the lines of a day file, lines starting with a digit are expected
+// to be activities, other lines are free notes
+pub fn lint_day(date: &str, content: &str) -> Vec<(usize, String)> {
+    let mut issues = Vec::new();
+    let mut previous: Vec<(usize, Activity)> = Vec::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        let line_number = idx + 1;
+
+        let activity = match parse_activity(date, line) {
+            Ok((_, activity)) => activity,
+            Err(_) => {
+                if line.trim_start().starts_with(|c: char| c.is_ascii_digit()) {
+                    issues.push((line_number, "can not parse activity".to_string()));
+                }
+                continue;
+            }
+        };
+
+        if activity.end_datetime <= activity.start_datetime {
+            issues.push((line_number, "ends before it starts".to_string()));
+        }
+
+        if has_several_actions(line) {
+            issues.push((line_number, "has several action tags".to_string()));
+        }
+
+        if let Some((_, last)) = previous.last() {
+            if activity.start_datetime < last.start_datetime {
+                issues.push((
+                    line_number,
+                    "starts before the previous activity".to_string(),
+                ));
+            }
+        }
+
+        for (other_line, other) in &previous {
+            if activity.start_datetime < other.end_datetime
+                && other.start_datetime < activity.end_datetime
+            {
+                issues.push((line_number, format!("overlaps line {}", other_line)));
+            }
+        }
+
+        previous.push((line_number, activity));
+    }
+
+    issues
+}
+
+// format_day rewrites the activity lines in the canonical format, other lines are kept,
+// as are lines with several action tags since an activity only keeps one of them
+pub fn format_day(date: &str, content: &str) -> String {
+    let mut formatted = content
+        .split('\n')
+        .map(|line| match parse_activity(date, line) {
+            Ok((_, activity)) if !has_several_actions(line) => activity.to_string(),
+            _ => line.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    if !formatted.is_empty() && !formatted.ends_with('\n') {
+        formatted.push('\n');
+    }
+
+    formatted
+}
+
+pub fn lint_dir(base_path: &str) -> Result<Vec<LintIssue>> {
+    let mut issues = Vec::new();
+
+    for (date, path) in day_files(base_path)? {
+        let content = read_to_string(&path)?;
+        issues.extend(
+            lint_day(&date, &content)
+                .into_iter()
+                .map(|(line, message)| LintIssue {
+                    path: path.to_string_lossy().to_string(),
+                    line,
+                    message,
+                }),
+        );
+    }
+
+    Ok(issues)
+}
+
+// format_dir returns the day files that are not formatted, they are rewritten unless check is set
+pub fn format_dir(base_path: &str, check: bool) -> Result<Vec<String>> {
+    let mut changed = Vec::new();
+
+    for (date, path) in day_files(base_path)? {
+        let content = read_to_string(&path)?;
+        let formatted = format_day(&date, &content);
+        if formatted == content {
+            continue;
+        }
+
+        if !check {
+            write_atomic(&path, &formatted)?;
+        }
+        changed.push(path.to_string_lossy().to_string());
+    }
+
+    Ok(changed)
+}
+
+fn has_several_actions(line: &str) -> bool {
+    line.split_once(':')
+        .and_then(|(_, tags)| parse_tags(tags).ok())
+        .is_some_and(|(_, types)| types.0.len() > 1)
+}
+
+// day_files lists the files named after a day, sorted by date
+fn day_files(base_path: &str) -> Result<Vec<(String, PathBuf)>> {
+    let mut day_files = Vec::new();
+
+    for f in read_dir(Path::new(base_path))? {
+        let path = f?.path();
+        let date = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if name.ends_with(".txt") => name.trim_end_matches(".txt").to_string(),
+            _ => continue,
+        };
+
+        if NaiveDate::parse_from_str(&date, "%Y.%m.%d").is_ok() {
+            day_files.push((date, path));
+        }
+    }
+    day_files.sort();
+
+    Ok(day_files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint_day() {
+        let content = "# standup
+09h00-10h00: [code][rust] loader
+09h30-09h45: [meeting] weekly
+11h00-10h00: [code] backwards
+1Oh00-11h00: [code] typo
+08h00-08h30: [docs] readme
+18h00-19h00: [code][review] pairing
+";
+
+        assert_eq!(
+            lint_day("2022.05.27", content),
+            vec![
+                (3, "overlaps line 2".to_string()),
+                (4, "ends before it starts".to_string()),
+                (5, "can not parse activity".to_string()),
+                (6, "starts before the previous activity".to_string()),
+                (7, "has several action tags".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_day() {
+        assert_eq!(
+            format_day(
+                "2022.05.27",
+                "# notes\n09h00 - 10h00 :[rust][code]   loader  \n\n14h00-15h00: [unknowntag] x"
+            ),
+            "# notes\n09h00-10h00: [code][rust] loader\n\n14h00-15h00: [unknowntag] x\n"
+        );
+
+        // only one action would be kept, the line is left for the user to fix
+        let content = "09h00 - 10h00: [code][review] [rust] pairing\n";
+        for _ in 0..10 {
+            assert_eq!(format_day("2022.05.27", content), content);
+        }
+    }
+}
@@ -1,40 +1,251 @@
-use crate::activity::Activities;
-use crate::parser::parse_activity;
-use eyre::Result;
+use crate::activity::{Activities, Activity, ActivityId};
+use crate::parser::{parse_activity, parse_date_heading};
+use chrono::NaiveDate;
+use eyre::{eyre, Result};
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::{read_dir, File};
 use std::io::{prelude::*, BufReader};
+use std::path::Path;
+use zip::ZipArchive;
+
+// a named journal root, e.g. a personal journal, a team journal or one folder per client
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize)]
+pub struct Source {
+    pub name: String,
+    pub path: String,
+}
 
 pub fn load_from_filepath(path: &str) -> Result<Activities> {
+    load_from_source(&Source {
+        name: path.to_string(),
+        path: path.to_string(),
+    })
+}
+
+pub fn load_from_source(source: &Source) -> Result<Activities> {
     let mut activities = Vec::new();
-    let files = read_dir(path)?;
+    let source_path = Path::new(&source.path);
+
+    // a single journal file or archive can be given instead of a folder
+    if source_path.is_file() {
+        load_from_path(source, source_path, &mut activities)?;
+        return Ok(activities.into());
+    }
+
+    let files = read_dir(source_path)?;
 
     for f in files {
         let f = f?;
-        let path = f.path();
-        let filename = path.file_name().unwrap().to_str().unwrap();
-        if let Some(ext) = path.extension() {
-            if ext != "txt" {
+        load_from_path(source, &f.path(), &mut activities)?;
+    }
+
+    Ok(activities.into())
+}
+
+// load_from_path reads a day file, a journal file, or the day files inside a zip or tar.gz archive
+fn load_from_path(source: &Source, path: &Path, activities: &mut Vec<Activity>) -> Result<()> {
+    let filename = path.file_name().unwrap().to_str().unwrap();
+
+    if filename.ends_with(".zip") {
+        return load_from_zip(source, path, activities);
+    }
+
+    if filename.ends_with(".tar.gz") || filename.ends_with(".tgz") {
+        return load_from_tar_gz(source, path, activities);
+    }
+
+    if !filename.ends_with(".txt") {
+        return Ok(());
+    }
+
+    let f = File::open(path)?;
+    load_from_reader(source, filename, BufReader::new(f), true, activities);
+
+    Ok(())
+}
+
+fn load_from_zip(source: &Source, path: &Path, activities: &mut Vec<Activity>) -> Result<()> {
+    let mut archive = ZipArchive::new(File::open(path)?)?;
+
+    for i in 0..archive.len() {
+        let f = archive.by_index(i)?;
+        if !f.is_file() {
+            continue;
+        }
+
+        let filename = archive_filename(f.name());
+        if filename.ends_with(".txt") {
+            load_from_reader(source, &filename, BufReader::new(f), false, activities);
+        }
+    }
+
+    Ok(())
+}
+
+fn load_from_tar_gz(source: &Source, path: &Path, activities: &mut Vec<Activity>) -> Result<()> {
+    let mut archive = tar::Archive::new(GzDecoder::new(File::open(path)?));
+
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+
+        let filename = archive_filename(&entry.path()?.to_string_lossy());
+        if filename.ends_with(".txt") {
+            load_from_reader(source, &filename, BufReader::new(entry), false, activities);
+        }
+    }
+
+    Ok(())
+}
+
+// archives keep their folders, only the file name carries the date
+fn archive_filename(name: &str) -> String {
+    name.rsplit('/').next().unwrap_or(name).to_string()
+}
+
+// load_from_reader parses a day file, or a journal with date headings when
+// the file is not named after a day. only the activities of day files the writer
+// can write back to, outside of archives, get an id
+fn load_from_reader<R: BufRead>(
+    source: &Source,
+    filename: &str,
+    reader: R,
+    writable: bool,
+    activities: &mut Vec<Activity>,
+) {
+    let date = filename.split(".txt").next().unwrap();
+    if NaiveDate::parse_from_str(date, "%Y.%m.%d").is_err() {
+        load_journal_lines(source, filename, reader, activities);
+        return;
+    }
+
+    for (idx, line_f) in reader.lines().enumerate() {
+        if let Ok(line) = line_f {
+            load_line(source, date, writable.then_some(idx + 1), &line, activities);
+        } else {
+            eprintln!("file {}: can not read line: {:?}", &filename, line_f);
+        }
+    }
+}
+
+// load_journal_lines reads one file where ## 2022.05.27 headings give the date of the lines below
+fn load_journal_lines<R: BufRead>(
+    source: &Source,
+    filename: &str,
+    reader: R,
+    activities: &mut Vec<Activity>,
+) {
+    let mut date: Option<String> = None;
+
+    for line_f in reader.lines() {
+        if let Ok(line) = line_f {
+            if let Ok((_, heading_date)) = parse_date_heading(&line) {
+                date = Some(heading_date);
                 continue;
             }
+
+            match &date {
+                Some(date) => load_line(source, date, None, &line, activities),
+                None => eprintln!("file {}: line before any date heading: {}", &filename, line),
+            }
         } else {
-            continue;
+            eprintln!("file {}: can not read line: {:?}", &filename, line_f);
+        }
+    }
+}
+
+fn load_line(
+    source: &Source,
+    date: &str,
+    line_number: Option<usize>,
+    line: &str,
+    activities: &mut Vec<Activity>,
+) {
+    match parse_activity(date, line) {
+        Ok((_, mut activity)) => {
+            activity.source = source.name.clone();
+            activity.id = line_number.map(|line_number| {
+                ActivityId::new(
+                    &source.name,
+                    activity.start_datetime.date(),
+                    line_number,
+                    &activity,
+                )
+            });
+            activities.push(activity)
         }
+        Err(err) => eprintln!("can not parse line: {} {}", line, err),
+    }
+}
 
-        let date = filename.split(".txt").next().unwrap();
-        let f = File::open(f.path())?;
-        let reader = BufReader::new(f);
+// source_path is the root to give the writer for an activity id
+pub fn source_path<'a>(sources: &'a [Source], id: &ActivityId) -> Result<&'a str> {
+    sources
+        .iter()
+        .find(|source| source.name == id.source)
+        .map(|source| source.path.as_str())
+        .ok_or_else(|| eyre!("no source named {} for activity {}", id.source, id))
+}
+
+// write_source is the root new lines and the timer state go to, the first source when
+// there are sources so what gets written is loaded back, else base_path named like
+// load_from_filepath names it
+pub fn write_source(base_path: &str, sources: &[Source]) -> Source {
+    sources.first().cloned().unwrap_or_else(|| Source {
+        name: base_path.to_string(),
+        path: base_path.to_string(),
+    })
+}
+
+// load_from_sources merges several roots, an activity found in more than one root
+// is kept once, attributed to the first root listing it
+pub fn load_from_sources(sources: &[Source]) -> Result<Activities> {
+    let mut activities: Vec<Activity> = Vec::new();
+    // only the roots already merged, the same line twice in one root is kept
+    let mut seen: HashMap<String, String> = HashMap::new();
 
-        for line_f in reader.lines() {
-            if let Ok(line) = line_f {
-                match parse_activity(date, line.clone().as_str()) {
-                    Ok((_, activity)) => activities.push(activity),
-                    Err(err) => println!("can not parse line: {} {}", line, err),
-                }
-            } else {
-                println!("file {}: can not read line: {:?}", &filename, line_f);
+    for source in sources {
+        let mut source_keys = Vec::new();
+        for activity in load_from_source(source)?.0 {
+            let key = duplicate_key(&activity);
+            if let Some(first_source) = seen.get(&key) {
+                eprintln!(
+                    "duplicate activity in {} and {}: {} {}",
+                    first_source,
+                    source.name,
+                    activity.start_datetime.date(),
+                    activity
+                );
+                continue;
             }
+            source_keys.push(key);
+            activities.push(activity);
         }
+        seen.extend(
+            source_keys
+                .into_iter()
+                .map(|key| (key, source.name.clone())),
+        );
     }
 
     Ok(activities.into())
 }
+
+// two activities are duplicates when everything but their source is the same
+fn duplicate_key(activity: &Activity) -> String {
+    let mut projects = activity.projects.iter().cloned().collect::<Vec<String>>();
+    projects.sort();
+
+    format!(
+        "{} {} {} {} {}",
+        activity.start_datetime,
+        activity.end_datetime,
+        activity.action,
+        projects.join(","),
+        activity.description
+    )
+}
@@ -1,6 +1,6 @@
 use crate::activity::{Action, Activity};
-use chrono::NaiveDateTime;
-use nom::bytes::complete::{take, take_until};
+use chrono::{NaiveDate, NaiveDateTime};
+use nom::bytes::complete::{tag, take, take_until};
 use nom::character::complete::{char, multispace0};
 use nom::combinator::map_res;
 use nom::multi::many0;
@@ -20,6 +20,19 @@ pub fn parse_time<'a>(date: &str, input: &'a str) -> IResult<&'a str, NaiveDateT
     )(input)
 }
 
+// parse_date_heading turns ## 2022.05.27 into the date of the lines that follow it
+pub fn parse_date_heading(input: &str) -> IResult<&str, String> {
+    let (input, date) = map_res(
+        preceded(
+            tag("##"),
+            delimited(multispace0, take(10usize), multispace0),
+        ),
+        |s: &str| NaiveDate::parse_from_str(s, "%Y.%m.%d").map(|_| s),
+    )(input)?;
+
+    Ok((input, date.to_string()))
+}
+
 pub fn parse_tag(input: &str) -> IResult<&str, String> {
     let (input, tag) = delimited(
         preceded(multispace0, char('[')),
@@ -30,11 +43,27 @@ pub fn parse_tag(input: &str) -> IResult<&str, String> {
     Ok((input, tag.trim().to_string()))
 }
 
-struct Types(HashSet<Action>, HashSet<String>);
+pub(crate) struct Types(pub(crate) HashSet<Action>, pub(crate) HashSet<String>);
 
-fn parse_tags(input: &str) -> IResult<&str, Types> {
-    let (input, tags) = many0(parse_tag)(input)?;
+impl Types {
+    // a line with several action tags always gives the same action, in the order of the enum,
+    // otherwise its id and its rewritten line would change between two reads
+    pub(crate) fn action(&self) -> Action {
+        [
+            Action::Review,
+            Action::Meeting,
+            Action::Research,
+            Action::Code,
+            Action::Docs,
+        ]
+        .into_iter()
+        .find(|action| self.0.contains(action))
+        .unwrap_or(Action::Unknown)
+    }
+}
 
+// tags_to_types sorts tags into actions and projects, importers share it with the day file format
+pub(crate) fn tags_to_types<'a>(tags: impl IntoIterator<Item = &'a str>) -> Types {
     let mut projects = HashSet::new();
     let mut actions = HashSet::new();
 
@@ -47,7 +76,13 @@ fn parse_tags(input: &str) -> IResult<&str, Types> {
         }
     }
 
-    Ok((input, Types(actions, projects)))
+    Types(actions, projects)
+}
+
+pub(crate) fn parse_tags(input: &str) -> IResult<&str, Types> {
+    let (input, tags) = many0(parse_tag)(input)?;
+
+    Ok((input, tags_to_types(tags.iter().map(|tag| tag.as_str()))))
 }
 
 // parse_activity turn 12h00-13h00: [tag1][tag2][tag3] description into an activity
@@ -70,8 +105,10 @@ pub fn parse_activity<'a>(date: &str, input: &'a str) -> IResult<&'a str, Activi
             start_datetime,
             end_datetime,
             description: description.to_string(),
-            action: types.0.into_iter().next().unwrap_or(Action::Unknown),
+            action: types.action(),
             projects: types.1,
+            source: String::new(),
+            id: None,
         },
     ))
 }
@@ -80,7 +117,6 @@ pub fn parse_activity<'a>(date: &str, input: &'a str) -> IResult<&'a str, Activi
 mod tests {
     use super::*;
     use crate::activity::Action;
-    use chrono::NaiveDate;
 
     #[test]
     fn test_parse_time() {
@@ -115,6 +151,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_date_heading() {
+        let (input, date) = parse_date_heading("## 2022.05.27").unwrap();
+        assert_eq!(input, "");
+        assert_eq!(date, "2022.05.27");
+
+        let (input, date) = parse_date_heading("##2022.05.27  ").unwrap();
+        assert_eq!(input, "");
+        assert_eq!(date, "2022.05.27");
+
+        assert!(parse_date_heading("## 2022.13.27").is_err());
+        assert!(parse_date_heading("# 2022.05.27").is_err());
+        assert!(parse_date_heading("09h00-09h30: [veille] news").is_err());
+    }
+
     #[test]
     fn test_parse_tag() {
         let (input, tag) = parse_tag("[tag1]").unwrap();
@@ -172,6 +223,15 @@ mod tests {
         assert!(tags.0.contains(&Action::Review));
     }
 
+    #[test]
+    fn test_parse_activity_several_actions() {
+        for _ in 0..10 {
+            let (_, activity) =
+                parse_activity("2022.07.05", "12h00-13h00: [code][docs][review] pairing").unwrap();
+            assert_eq!(activity.action, Action::Review);
+        }
+    }
+
     #[test]
     fn test_parse_activity() {
         let target_act = Activity {
@@ -186,6 +246,8 @@ mod tests {
             description: "description".to_string(),
             action: Action::Review,
             projects: ["tag2".to_string(), "tag3".to_string()].into(),
+            source: String::new(),
+            id: None,
         };
         let target_act_dash = Activity {
             start_datetime: NaiveDate::from_ymd_opt(2022, 7, 5)
@@ -204,6 +266,8 @@ mod tests {
                 "re-tash-yo".to_string(),
             ]
             .into(),
+            source: String::new(),
+            id: None,
         };
         let target_act_spaces = Activity {
             start_datetime: NaiveDate::from_ymd_opt(2022, 7, 5)
@@ -217,6 +281,8 @@ mod tests {
             description: "description of my tests".to_string(),
             action: Action::Review,
             projects: ["tag2".to_string(), "tag3".to_string()].into(),
+            source: String::new(),
+            id: None,
         };
         let target_emptydesc = Activity {
             start_datetime: NaiveDate::from_ymd_opt(2022, 7, 5)
@@ -230,6 +296,8 @@ mod tests {
             description: "".to_string(),
             action: Action::Review,
             projects: ["tag2".to_string(), "tag3".to_string()].into(),
+            source: String::new(),
+            id: None,
         };
 
         let test_cases = vec![
@@ -0,0 +1,288 @@
+use crate::activity::{Action, ActivitiesAggregate, Activity};
+use crate::parser::tags_to_types;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use eyre::{eyre, Result};
+
+// parse_shorthand turns quick notes into an activity:
+//   30m code rust fixed loader             30 minutes ending now
+//   1h30 meeting planning ended 15m ago    ending 15 minutes ago
+//   14h-15h30 meeting planning             a range of the given date
+//   16h-now docs readme                    a range ending now
+// the leading words that are actions, known projects or [tags] become the tags,
+// now is taken at the time of day of now on the given date
+pub fn parse_shorthand(
+    input: &str,
+    known: &ActivitiesAggregate,
+    date: &NaiveDate,
+    now: &NaiveDateTime,
+) -> Result<Activity> {
+    let now = date.and_time(
+        now.time()
+            .with_second(0)
+            .unwrap()
+            .with_nanosecond(0)
+            .unwrap(),
+    );
+    let mut words = input.split_whitespace().collect::<Vec<&str>>();
+    if words.is_empty() {
+        return Err(eyre!("nothing to add"));
+    }
+    let when = words.remove(0);
+
+    let (start_datetime, end_datetime) = if let Some((start, end)) = parse_range(when) {
+        let end_datetime = match end {
+            Some(end) => date.and_time(end),
+            None => now,
+        };
+        (end_datetime.date().and_time(start), end_datetime)
+    } else if let Some(duration) = parse_duration(when) {
+        let end_datetime = now
+            .checked_sub_signed(take_ago(&mut words))
+            .ok_or_else(|| eyre!("the activity ends before the first date"))?;
+        let start_datetime = end_datetime
+            .checked_sub_signed(duration)
+            .ok_or_else(|| eyre!("the activity starts before the first date"))?;
+        (start_datetime, end_datetime)
+    } else {
+        return Err(eyre!(
+            "{} is not a duration like 30m or a range like 14h-15h30",
+            when
+        ));
+    };
+
+    if end_datetime <= start_datetime {
+        return Err(eyre!("the activity ends before it starts"));
+    }
+    if end_datetime.date() != start_datetime.date() {
+        return Err(eyre!("the activity spans over midnight"));
+    }
+
+    let tags_len = words.iter().take_while(|word| is_tag(word, known)).count();
+    let types = tags_to_types(
+        words[..tags_len]
+            .iter()
+            .map(|word| word.trim_start_matches('[').trim_end_matches(']')),
+    );
+
+    Ok(Activity {
+        start_datetime,
+        end_datetime,
+        description: words[tags_len..].join(" "),
+        action: types.action(),
+        projects: types.1,
+        source: String::new(),
+        id: None,
+    })
+}
+
+fn is_tag(word: &str, known: &ActivitiesAggregate) -> bool {
+    (word.starts_with('[') && word.ends_with(']'))
+        || word.parse::<Action>().is_ok()
+        || known.3.contains(word)
+}
+
+// take_ago removes "ended now", "ended 30m ago" or "30m ago" from the start or the end
+// of the words, so a description like "revert 5m ago change" is kept as is
+fn take_ago(words: &mut Vec<&str>) -> Duration {
+    for len in [3, 2] {
+        if words.len() < len {
+            continue;
+        }
+
+        for start in [0, words.len() - len] {
+            if let Some(duration) = parse_ago(&words[start..start + len]) {
+                words.drain(start..start + len);
+                return duration;
+            }
+        }
+    }
+
+    Duration::zero()
+}
+
+fn parse_ago(words: &[&str]) -> Option<Duration> {
+    match words {
+        ["ended", "now"] => Some(Duration::zero()),
+        ["ended", ago, "ago"] | [ago, "ago"] => parse_duration(ago),
+        _ => None,
+    }
+}
+
+// parse_duration reads 30m, 90m, 1h, 1h30 or 1h30m, up to a day
+fn parse_duration(input: &str) -> Option<Duration> {
+    let minutes = match input.split_once('h') {
+        Some((hours, minutes)) => {
+            let minutes = minutes.strip_suffix('m').unwrap_or(minutes);
+            let minutes = if minutes.is_empty() {
+                0
+            } else {
+                number(minutes)?
+            };
+            if minutes >= 60 {
+                return None;
+            }
+            number(hours)?.checked_mul(60)?.checked_add(minutes)?
+        }
+        // a bare number needs its m suffix, otherwise it is a word of the description
+        None => number(input.strip_suffix('m')?)?,
+    };
+
+    (minutes > 0 && minutes <= 24 * 60).then(|| Duration::minutes(minutes))
+}
+
+// parse_range reads 14h-15h30 or 14h-now, None as end means now
+fn parse_range(input: &str) -> Option<(NaiveTime, Option<NaiveTime>)> {
+    let (start, end) = input.split_once('-')?;
+    let start = parse_time(start)?;
+    let end = match end {
+        "now" => None,
+        end => Some(parse_time(end)?),
+    };
+
+    Some((start, end))
+}
+
+// parse_time reads 9h, 14h or 14h30
+fn parse_time(input: &str) -> Option<NaiveTime> {
+    let (hours, minutes) = input.split_once('h')?;
+    let minutes = if minutes.is_empty() {
+        0
+    } else {
+        number(minutes)?
+    };
+
+    NaiveTime::from_hms_opt(
+        u32::try_from(number(hours)?).ok()?,
+        u32::try_from(minutes).ok()?,
+        0,
+    )
+}
+
+fn number(input: &str) -> Option<i64> {
+    if input.is_empty() || !input.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+
+    input.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn datetime(h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2022, 5, 27)
+            .unwrap()
+            .and_hms_opt(h, min, 0)
+            .unwrap()
+    }
+
+    fn parse(input: &str) -> Result<Activity> {
+        let known = ActivitiesAggregate(
+            NaiveDate::from_ymd_opt(2022, 5, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2022, 5, 26).unwrap(),
+            HashSet::new(),
+            ["rust".to_string(), "planning".to_string()].into(),
+            HashSet::new(),
+        );
+
+        parse_shorthand(
+            input,
+            &known,
+            &NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+            &(datetime(16, 20) + Duration::seconds(42)),
+        )
+    }
+
+    #[test]
+    fn test_parse_shorthand() {
+        let activity = parse("30m code rust fixed loader").unwrap();
+        assert_eq!(activity.start_datetime, datetime(15, 50));
+        assert_eq!(activity.end_datetime, datetime(16, 20));
+        assert_eq!(activity.action, Action::Code);
+        assert_eq!(activity.projects, ["rust".to_string()].into());
+        assert_eq!(activity.description, "fixed loader");
+
+        let activity = parse("14h-15h30 meeting planning").unwrap();
+        assert_eq!(
+            activity.to_string(),
+            "14h00-15h30: [meeting][planning]".to_string()
+        );
+        assert_eq!(
+            activity.start_datetime.date(),
+            NaiveDate::from_ymd_opt(2022, 5, 27).unwrap()
+        );
+
+        let activity = parse("1h30 [docs] [newproject] readme ended 30m ago").unwrap();
+        assert_eq!(
+            activity.to_string(),
+            "14h20-15h50: [docs][newproject] readme".to_string()
+        );
+
+        let activity = parse("45m review 2 prs ended now").unwrap();
+        assert_eq!(activity.start_datetime, datetime(15, 35));
+        assert_eq!(activity.description, "2 prs");
+
+        let activity = parse("16h-now research rust").unwrap();
+        assert_eq!(activity.start_datetime, datetime(16, 0));
+        assert_eq!(activity.end_datetime, datetime(16, 20));
+
+        let activity = parse("30m ended 10m ago code revert 5m ago change").unwrap();
+        assert_eq!(activity.end_datetime, datetime(16, 10));
+        assert_eq!(activity.description, "revert 5m ago change");
+
+        let activity = parse("15m code revert 5m ago change").unwrap();
+        assert_eq!(activity.end_datetime, datetime(16, 20));
+        assert_eq!(activity.description, "revert 5m ago change");
+
+        // the duration ends at the time of day of now on the given date
+        let activity = parse_shorthand(
+            "30m code",
+            &ActivitiesAggregate(
+                NaiveDate::from_ymd_opt(2022, 5, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2022, 5, 26).unwrap(),
+                HashSet::new(),
+                HashSet::new(),
+                HashSet::new(),
+            ),
+            &NaiveDate::from_ymd_opt(2022, 5, 20).unwrap(),
+            &datetime(16, 20),
+        )
+        .unwrap();
+        assert_eq!(
+            activity.start_datetime,
+            NaiveDate::from_ymd_opt(2022, 5, 20)
+                .unwrap()
+                .and_hms_opt(15, 50, 0)
+                .unwrap()
+        );
+
+        assert!(parse("code rust").is_err());
+        assert!(parse("17h-now code").is_err());
+        assert!(parse("20h ago code").is_err());
+        assert!(parse("999999999999h code").is_err());
+        assert!(parse("9h4294967306-10h code").is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_duration("1h"), Some(Duration::minutes(60)));
+        assert_eq!(parse_duration("1h30"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::minutes(90)));
+        assert_eq!(parse_duration("30"), None);
+        assert_eq!(parse_duration("1h75"), None);
+        assert_eq!(parse_duration("-5m"), None);
+        assert_eq!(parse_duration("24h"), Some(Duration::hours(24)));
+        assert_eq!(parse_duration("24h1"), None);
+        assert_eq!(parse_duration("99999999999999999h"), None);
+    }
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("14h30"), NaiveTime::from_hms_opt(14, 30, 0));
+        assert_eq!(parse_time("9h4294967306"), None);
+        assert_eq!(parse_time("4294967305h"), None);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::activity::{Action, Activity};
+use chrono::NaiveDate;
+use std::fs::{create_dir_all, remove_dir_all};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static TEST_DIRS: AtomicUsize = AtomicUsize::new(0);
+
+// activity builds an activity of may 2022 from the source work
+pub(crate) fn activity(
+    d: u32,
+    start: (u32, u32),
+    end: (u32, u32),
+    action: Action,
+    projects: &[&str],
+    description: &str,
+) -> Activity {
+    Activity {
+        start_datetime: NaiveDate::from_ymd_opt(2022, 5, d)
+            .unwrap()
+            .and_hms_opt(start.0, start.1, 0)
+            .unwrap(),
+        end_datetime: NaiveDate::from_ymd_opt(2022, 5, d)
+            .unwrap()
+            .and_hms_opt(end.0, end.1, 0)
+            .unwrap(),
+        description: description.to_string(),
+        action,
+        projects: projects.iter().map(|project| project.to_string()).collect(),
+        source: "work".to_string(),
+        id: None,
+    }
+}
+
+// TestDir is an empty folder removed when the test is over
+pub(crate) struct TestDir(PathBuf);
+
+impl TestDir {
+    pub(crate) fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TestDir {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.0);
+    }
+}
+
+// test_dir gives each call its own folder, the process id keeps parallel runs apart
+pub(crate) fn test_dir(name: &str) -> TestDir {
+    let dir = std::env::temp_dir().join(format!(
+        "timespent-{}-{}-{}",
+        name,
+        std::process::id(),
+        TEST_DIRS.fetch_add(1, Ordering::Relaxed)
+    ));
+    create_dir_all(&dir).unwrap();
+
+    TestDir(dir)
+}
@@ -0,0 +1,175 @@
+use crate::activity::{Action, Activity, ActivityId};
+use crate::loader::Source;
+use crate::parser::parse_tags;
+use crate::writer::{insert_activity, split_at_midnight, write_atomic};
+use chrono::{NaiveDateTime, Timelike};
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fs::{read_to_string, remove_file};
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+// the state file sits next to the day files, the loader only reads .txt files
+const STATE_FILE: &str = ".timespent-running.json";
+
+// RunningActivity is an activity that was started and not stopped yet
+#[derive(Eq, PartialEq, Debug, Clone, Deserialize, Serialize, TS)]
+#[ts(export)]
+pub struct RunningActivity {
+    pub start_datetime: NaiveDateTime,
+    pub description: String,
+    pub action: Action,
+    pub projects: HashSet<String>,
+}
+
+impl RunningActivity {
+    pub fn elapsed_minutes(&self, now: &NaiveDateTime) -> i64 {
+        (*now - self.start_datetime).num_minutes()
+    }
+}
+
+impl Display for RunningActivity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let activity = Activity {
+            start_datetime: self.start_datetime,
+            end_datetime: self.start_datetime,
+            description: self.description.clone(),
+            action: self.action,
+            projects: self.projects.clone(),
+            source: String::new(),
+            id: None,
+        };
+
+        // the line of the activity with the end left out
+        let line = activity.to_string();
+        write!(f, "since {}{}", &line[..5], &line[11..])
+    }
+}
+
+pub fn running(base_path: &str) -> Result<Option<RunningActivity>> {
+    let path = state_path(base_path);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    Ok(Some(serde_json::from_str(&read_to_string(path)?)?))
+}
+
+// start records a running activity from tags and a description like [code][rust] fixing parser,
+// the activity already running is stopped first and returned
+pub fn start(
+    source: &Source,
+    input: &str,
+    now: &NaiveDateTime,
+) -> Result<(RunningActivity, Option<Vec<ActivityId>>)> {
+    let (description, types) =
+        parse_tags(input.trim()).map_err(|err| eyre!("can not parse {}: {}", input, err))?;
+
+    let stopped = stop(source, now)?;
+
+    let running = RunningActivity {
+        start_datetime: truncate(now),
+        description: description.trim().to_string(),
+        action: types.action(),
+        projects: types.1,
+    };
+    write_atomic(&state_path(&source.path), &serde_json::to_string(&running)?)?;
+
+    Ok((running, stopped))
+}
+
+// stop writes the running activity in its day file, an activity running past midnight
+// is split in one activity per day like imports are
+pub fn stop(source: &Source, now: &NaiveDateTime) -> Result<Option<Vec<ActivityId>>> {
+    let running = match running(&source.path)? {
+        Some(running) => running,
+        None => return Ok(None),
+    };
+
+    let activity = Activity {
+        start_datetime: running.start_datetime,
+        end_datetime: truncate(now),
+        description: running.description,
+        action: running.action,
+        projects: running.projects,
+        source: String::new(),
+        id: None,
+    };
+    let ids = split_at_midnight(&activity)
+        .iter()
+        .map(|activity| insert_activity(source, activity))
+        .collect::<Result<Vec<ActivityId>>>()?;
+
+    remove_file(state_path(&source.path))?;
+
+    Ok(Some(ids))
+}
+
+fn state_path(base_path: &str) -> PathBuf {
+    Path::new(base_path).join(STATE_FILE)
+}
+
+// day files have a minute precision
+fn truncate(datetime: &NaiveDateTime) -> NaiveDateTime {
+    datetime.with_second(0).unwrap().with_nanosecond(0).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_dir;
+    use chrono::NaiveDate;
+
+    fn datetime(d: u32, h: u32, min: u32, sec: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2022, 5, d)
+            .unwrap()
+            .and_hms_opt(h, min, sec)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_start_stop() {
+        let tmp = test_dir("timer-start-stop");
+        let dir = tmp.path();
+        let source = Source {
+            name: "work".to_string(),
+            path: dir.to_string(),
+        };
+
+        assert_eq!(running(&dir).unwrap(), None);
+        assert_eq!(stop(&source, &datetime(27, 9, 0, 0)).unwrap(), None);
+
+        let (started, stopped) = start(
+            &source,
+            "[rust][code] fixing parser",
+            &datetime(27, 9, 12, 40),
+        )
+        .unwrap();
+        assert_eq!(stopped, None);
+        assert_eq!(
+            started.to_string(),
+            "since 09h12: [code][rust] fixing parser"
+        );
+        assert_eq!(running(&dir).unwrap(), Some(started.clone()));
+        assert_eq!(started.elapsed_minutes(&datetime(27, 10, 17, 0)), 65);
+
+        // starting another activity stops the running one
+        let (_, stopped) = start(&source, "[meeting] weekly", &datetime(27, 10, 30, 5)).unwrap();
+        assert_eq!(stopped.unwrap().len(), 1);
+
+        let ids = stop(&source, &datetime(28, 1, 0, 0)).unwrap().unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(running(&dir).unwrap(), None);
+
+        assert_eq!(
+            read_to_string(Path::new(&dir).join("2022.05.27.txt")).unwrap(),
+            "09h12-10h30: [code][rust] fixing parser\n10h30-23h59: [meeting] weekly\n"
+        );
+        assert_eq!(
+            read_to_string(Path::new(&dir).join("2022.05.28.txt")).unwrap(),
+            "00h00-01h00: [meeting] weekly\n"
+        );
+    }
+}
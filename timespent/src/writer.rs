@@ -0,0 +1,396 @@
+use crate::activity::{Action, Activity, ActivityId};
+use crate::loader::Source;
+use crate::parser::{parse_activity, parse_tags};
+use chrono::{Duration, NaiveDate};
+use eyre::{eyre, Result};
+use std::fs::{rename, File};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+// insert_activity writes the activity in its day file, before the first activity starting later,
+// the id is named after the source like the ids of the loader
+pub fn insert_activity(source: &Source, activity: &Activity) -> Result<ActivityId> {
+    let date = activity.start_datetime.date();
+    let mut day_file = DayFile::read(&source.path, &date)?;
+
+    let activity_lines = day_file.activity_lines();
+    let idx = match activity_lines
+        .iter()
+        .find(|(_, act)| act.start_datetime > activity.start_datetime)
+    {
+        Some((idx, _)) => *idx,
+        None => match activity_lines.last() {
+            Some((idx, _)) => idx + 1,
+            None => day_file.content_len(),
+        },
+    };
+
+    day_file.lines.insert(idx, activity.to_string());
+    day_file.write()?;
+
+    Ok(ActivityId::new(&source.name, date, idx + 1, activity))
+}
+
+// split_at_midnight cuts an activity running past midnight in one activity per day,
+// so each day file only holds times of its own day. A day ends at 23h59 so a minute is
+// lost per midnight crossed, and pieces under a minute are left out
+pub(crate) fn split_at_midnight(activity: &Activity) -> Vec<Activity> {
+    let mut activities = Vec::new();
+    let mut start_datetime = activity.start_datetime;
+    while start_datetime < activity.end_datetime {
+        let midnight = (start_datetime.date() + Duration::days(1))
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        // the last minute of the day is written as 23h59, the format has no 24h00
+        let day_end = if activity.end_datetime < midnight {
+            activity.end_datetime
+        } else {
+            midnight - Duration::minutes(1)
+        };
+
+        if (day_end - start_datetime).num_minutes() > 0 {
+            activities.push(Activity {
+                start_datetime,
+                end_datetime: day_end,
+                ..activity.clone()
+            });
+        }
+        start_datetime = midnight;
+    }
+
+    activities
+}
+
+// contains_activity tells if the day file already has an activity with the same times and description
+pub(crate) fn contains_activity(base_path: &str, activity: &Activity) -> Result<bool> {
+    let day_file = DayFile::read(base_path, &activity.start_datetime.date())?;
+
+    Ok(day_file.activity_lines().iter().any(|(_, act)| {
+        act.start_datetime == activity.start_datetime
+            && act.end_datetime == activity.end_datetime
+            && act.description == activity.description
+    }))
+}
+
+// edit_activity replaces the activity line, moving it to another day file if its date changed
+pub fn edit_activity(base_path: &str, id: &ActivityId, activity: &Activity) -> Result<ActivityId> {
+    let mut day_file = DayFile::read(base_path, &id.date)?;
+    let idx = day_file.find(id)?;
+
+    // the new line is written before the old one is removed, a failed write
+    // leaves the activity twice rather than not at all
+    if activity.start_datetime.date() != id.date {
+        let source = Source {
+            name: id.source.clone(),
+            path: base_path.to_string(),
+        };
+        let new_id = insert_activity(&source, activity)?;
+        day_file.lines.remove(idx);
+        day_file.write()?;
+
+        return Ok(new_id);
+    }
+
+    day_file.lines[idx] = with_tags_of(&day_file.lines[idx], activity);
+    day_file.write()?;
+
+    Ok(ActivityId::new(&id.source, id.date, idx + 1, activity))
+}
+
+// with_tags_of keeps the tags of the line when the activity still has them, an activity
+// only holds one of the action tags of a line like [code][review], the others would be lost
+fn with_tags_of(line: &str, activity: &Activity) -> String {
+    let tags = match line.split_once(':') {
+        Some((_, tags)) => tags,
+        None => return activity.to_string(),
+    };
+    let (rest, types) = match parse_tags(tags) {
+        Ok(parsed) => parsed,
+        Err(_) => return activity.to_string(),
+    };
+
+    let same_action = types.0.contains(&activity.action)
+        || types.0.is_empty() && activity.action == Action::Unknown;
+    if !same_action || types.1 != activity.projects {
+        return activity.to_string();
+    }
+
+    let line = format!(
+        "{}-{}: {} {}",
+        activity.start_datetime.format("%Hh%M"),
+        activity.end_datetime.format("%Hh%M"),
+        tags[..tags.len() - rest.len()].trim(),
+        activity.description,
+    );
+
+    line.trim_end().to_string()
+}
+
+pub fn delete_activity(base_path: &str, id: &ActivityId) -> Result<()> {
+    let mut day_file = DayFile::read(base_path, &id.date)?;
+    let idx = day_file.find(id)?;
+
+    day_file.lines.remove(idx);
+    day_file.write()
+}
+
+// DayFile keeps every line of a day file as is, so comments and formatting
+// of the lines not being written survive
+struct DayFile {
+    path: PathBuf,
+    date: String,
+    lines: Vec<String>,
+}
+
+impl DayFile {
+    fn read(base_path: &str, date: &NaiveDate) -> Result<DayFile> {
+        let date = date.format("%Y.%m.%d").to_string();
+        let path = Path::new(base_path).join(format!("{}.txt", date));
+
+        let mut content = String::new();
+        if path.exists() {
+            File::open(&path)?.read_to_string(&mut content)?;
+        }
+
+        let lines = if content.is_empty() {
+            Vec::new()
+        } else {
+            content.split('\n').map(|line| line.to_string()).collect()
+        };
+
+        Ok(DayFile { path, date, lines })
+    }
+
+    fn activity_lines(&self) -> Vec<(usize, Activity)> {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, line)| match parse_activity(&self.date, line) {
+                Ok((_, activity)) => Some((idx, activity)),
+                Err(_) => None,
+            })
+            .collect()
+    }
+
+    // number of lines before the trailing newline
+    fn content_len(&self) -> usize {
+        match self.lines.last() {
+            Some(line) if line.is_empty() => self.lines.len() - 1,
+            _ => self.lines.len(),
+        }
+    }
+
+    // find looks at the line of the id first, then anywhere in the file
+    fn find(&self, id: &ActivityId) -> Result<usize> {
+        let activity_lines = self.activity_lines();
+
+        activity_lines
+            .iter()
+            .find(|(idx, act)| idx + 1 == id.line && id.matches(act))
+            .or_else(|| activity_lines.iter().find(|(_, act)| id.matches(act)))
+            .map(|(idx, _)| *idx)
+            .ok_or_else(|| eyre!("activity {} not found", id))
+    }
+
+    // write goes through a temp file renamed over the day file, so a failed
+    // write never leaves a half written day
+    fn write(&self) -> Result<()> {
+        let mut content = self.lines.join("\n");
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+
+        write_atomic(&self.path, &content)
+    }
+}
+
+// write_atomic goes through a temp file renamed over the file
+pub(crate) fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    let tmp_path = path.with_extension("txt.tmp");
+    let mut tmp_f = File::create(&tmp_path)?;
+    tmp_f.write_all(content.as_bytes())?;
+    tmp_f.sync_all()?;
+    rename(&tmp_path, path)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{activity, test_dir};
+    use std::fs::read_to_string;
+
+    #[test]
+    fn test_insert_activity() {
+        let tmp = test_dir("writer-insert");
+        let dir = tmp.path();
+        let source = Source {
+            name: "work".to_string(),
+            path: dir.to_string(),
+        };
+        let day_path = Path::new(&dir).join("2022.05.27.txt");
+        std::fs::write(
+            &day_path,
+            "09h00-09h30: [veille]  news\n\n14h00-15h45: [rust][code] api\n\ncomments\n",
+        )
+        .unwrap();
+
+        let id = insert_activity(
+            &source,
+            &activity(27, (10, 0), (11, 0), Action::Code, &["rust"], "loader"),
+        )
+        .unwrap();
+        assert_eq!(id.line, 3);
+        assert_eq!(id.source, "work");
+        let id = insert_activity(
+            &source,
+            &activity(27, (16, 0), (17, 0), Action::Code, &["rust"], "parser"),
+        )
+        .unwrap();
+        assert_eq!(id.line, 5);
+
+        assert_eq!(
+            read_to_string(&day_path).unwrap(),
+            "09h00-09h30: [veille]  news\n\n10h00-11h00: [code][rust] loader\n14h00-15h45: [rust][code] api\n16h00-17h00: [code][rust] parser\n\ncomments\n"
+        );
+
+        let id = insert_activity(
+            &source,
+            &Activity {
+                start_datetime: NaiveDate::from_ymd_opt(2022, 5, 28)
+                    .unwrap()
+                    .and_hms_opt(9, 0, 0)
+                    .unwrap(),
+                end_datetime: NaiveDate::from_ymd_opt(2022, 5, 28)
+                    .unwrap()
+                    .and_hms_opt(9, 30, 0)
+                    .unwrap(),
+                description: "news".to_string(),
+                action: Action::Research,
+                projects: ["veille".to_string()].into(),
+                source: String::new(),
+                id: None,
+            },
+        )
+        .unwrap();
+        assert_eq!(id.line, 1);
+        assert_eq!(
+            read_to_string(Path::new(&dir).join("2022.05.28.txt")).unwrap(),
+            "09h00-09h30: [research][veille] news\n"
+        );
+    }
+
+    #[test]
+    fn test_edit_delete_activity() {
+        let tmp = test_dir("writer-edit");
+        let dir = tmp.path();
+        let source = Source {
+            name: "work".to_string(),
+            path: dir.to_string(),
+        };
+        let day_path = Path::new(&dir).join("2022.05.27.txt");
+        std::fs::write(&day_path, "# standup\n10h00-11h00: [rust][code] loader\n").unwrap();
+
+        let id = ActivityId::new(
+            "work",
+            NaiveDate::from_ymd_opt(2022, 5, 27).unwrap(),
+            2,
+            &activity(27, (10, 0), (11, 0), Action::Code, &["rust"], "loader"),
+        );
+
+        // a line added above the activity does not lose it
+        insert_activity(
+            &source,
+            &activity(27, (9, 0), (10, 0), Action::Code, &["rust"], "parser"),
+        )
+        .unwrap();
+
+        let id = edit_activity(
+            &dir,
+            &id,
+            &activity(27, (10, 0), (11, 30), Action::Code, &["rust"], "loader"),
+        )
+        .unwrap();
+        assert_eq!(id.line, 3);
+        assert_eq!(
+            read_to_string(&day_path).unwrap(),
+            "# standup\n09h00-10h00: [code][rust] parser\n10h00-11h30: [rust][code] loader\n"
+        );
+
+        delete_activity(&dir, &id).unwrap();
+        assert_eq!(
+            read_to_string(&day_path).unwrap(),
+            "# standup\n09h00-10h00: [code][rust] parser\n"
+        );
+
+        assert!(delete_activity(&dir, &id).is_err());
+    }
+
+    #[test]
+    fn test_edit_activity_date() {
+        let tmp = test_dir("writer-edit-date");
+        let dir = tmp.path();
+        let day_path = Path::new(&dir).join("2022.05.27.txt");
+        std::fs::write(
+            &day_path,
+            "10h00-11h00: [code][rust] loader
+",
+        )
+        .unwrap();
+
+        let loaded = activity(27, (10, 0), (11, 0), Action::Code, &["rust"], "loader");
+        let id = ActivityId::new("work", loaded.start_datetime.date(), 1, &loaded);
+        let moved = activity(28, (10, 0), (11, 0), Action::Code, &["rust"], "loader");
+
+        // the old line stays when the new day can not be written
+        std::fs::create_dir(Path::new(&dir).join("2022.05.28.txt")).unwrap();
+        assert!(edit_activity(dir, &id, &moved).is_err());
+        assert_eq!(
+            read_to_string(&day_path).unwrap(),
+            "10h00-11h00: [code][rust] loader
+"
+        );
+
+        std::fs::remove_dir(Path::new(&dir).join("2022.05.28.txt")).unwrap();
+        let id = edit_activity(dir, &id, &moved).unwrap();
+        assert_eq!(id.source, "work");
+        assert_eq!(read_to_string(&day_path).unwrap(), "");
+        assert_eq!(
+            read_to_string(Path::new(&dir).join("2022.05.28.txt")).unwrap(),
+            "10h00-11h00: [code][rust] loader
+"
+        );
+    }
+
+    #[test]
+    fn test_edit_keeps_action_tags() {
+        let tmp = test_dir("writer-edit-tags");
+        let dir = tmp.path();
+        let day_path = Path::new(&dir).join("2022.05.27.txt");
+        std::fs::write(&day_path, "10h00-11h00: [review][rust][code] pairing\n").unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2022, 5, 27).unwrap();
+        let (_, original) =
+            parse_activity("2022.05.27", "10h00-11h00: [review][rust][code] pairing").unwrap();
+        let id = ActivityId::new(&dir, date, 1, &original);
+
+        let mut edited = original.clone();
+        edited.end_datetime = date.and_hms_opt(11, 30, 0).unwrap();
+        edited.description = "pairing on the loader".to_string();
+
+        let id = edit_activity(&dir, &id, &edited).unwrap();
+        assert_eq!(
+            read_to_string(&day_path).unwrap(),
+            "10h00-11h30: [review][rust][code] pairing on the loader\n"
+        );
+
+        // a changed action is written as the canonical line
+        edited.action = Action::Meeting;
+        edit_activity(&dir, &id, &edited).unwrap();
+        assert_eq!(
+            read_to_string(&day_path).unwrap(),
+            "10h00-11h30: [meeting][rust] pairing on the loader\n"
+        );
+    }
+}
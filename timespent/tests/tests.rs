@@ -1,4 +1,5 @@
-use timespent::loader;
+use chrono::{Datelike, NaiveDate};
+use timespent::loader::{self, Source};
 
 #[test]
 fn test_loader() {
@@ -15,3 +16,129 @@ fn test_aggregates() {
 
     assert_eq!(agg.2.len(), 4);
 }
+
+#[test]
+fn test_loader_sources() {
+    let activities = loader::load_from_sources(&[
+        Source {
+            name: "personal".to_string(),
+            path: "tests/days".to_string(),
+        },
+        Source {
+            name: "team".to_string(),
+            path: "tests/team".to_string(),
+        },
+    ])
+    .unwrap();
+    assert_eq!(activities.0.len(), 17);
+
+    let agg = activities.aggregate_all();
+    assert_eq!(agg.4, ["personal".to_string(), "team".to_string()].into());
+}
+
+#[test]
+fn test_loader_sources_duplicates() {
+    // the same line twice in one root is kept, a line already in an earlier root is not
+    let activities = loader::load_from_sources(&[
+        Source {
+            name: "team".to_string(),
+            path: "tests/team".to_string(),
+        },
+        Source {
+            name: "twice".to_string(),
+            path: "tests/twice".to_string(),
+        },
+    ])
+    .unwrap();
+    assert_eq!(activities.0.len(), 2 + 2);
+}
+
+#[test]
+fn test_loader_journal() {
+    let activities = loader::load_from_filepath("tests/journal").unwrap();
+    assert_eq!(activities.0.len(), 5);
+
+    let agg = activities.aggregate_all();
+    assert_eq!(agg.0, NaiveDate::from_ymd_opt(2022, 5, 27).unwrap());
+    assert_eq!(agg.1, NaiveDate::from_ymd_opt(2022, 5, 30).unwrap());
+
+    let activities = loader::load_from_filepath("tests/journal/journal.txt").unwrap();
+    assert_eq!(activities.0.len(), 5);
+}
+
+#[test]
+fn test_loader_archives() {
+    let activities = loader::load_from_filepath("tests/archives").unwrap();
+    assert_eq!(activities.0.len(), 4);
+
+    let agg = activities.aggregate_all();
+    assert_eq!(agg.0, NaiveDate::from_ymd_opt(2020, 12, 31).unwrap());
+    assert_eq!(agg.1, NaiveDate::from_ymd_opt(2022, 1, 3).unwrap());
+
+    let activities = loader::load_from_filepath("tests/archives/2021.zip").unwrap();
+    assert_eq!(activities.0.len(), 2);
+}
+
+#[test]
+fn test_loader_ids() {
+    let activities = loader::load_from_filepath("tests/days").unwrap();
+    let reloaded = loader::load_from_filepath("tests/days").unwrap();
+
+    let activity = activities
+        .0
+        .iter()
+        .find(|act| act.description == "with MT" && act.start_datetime.day() == 27)
+        .unwrap();
+    let id = activity.id.clone().unwrap();
+    assert_eq!(id.date, NaiveDate::from_ymd_opt(2022, 5, 27).unwrap());
+    assert_eq!(id.line, 8);
+    assert_eq!(id.source, "tests/days");
+    assert!(reloaded.0.iter().any(|act| act.id == Some(id.clone())));
+
+    // journal and archive lines can not be written back
+    let journal = loader::load_from_filepath("tests/journal").unwrap();
+    assert!(journal.0.iter().all(|act| act.id.is_none()));
+    let archives = loader::load_from_filepath("tests/archives/2021.zip").unwrap();
+    assert!(archives.0.iter().all(|act| act.id.is_none()));
+}
+
+#[test]
+fn test_source_path() {
+    let sources = [
+        Source {
+            name: "personal".to_string(),
+            path: "tests/days".to_string(),
+        },
+        Source {
+            name: "team".to_string(),
+            path: "tests/team".to_string(),
+        },
+    ];
+    let activities = loader::load_from_sources(&sources).unwrap();
+
+    let team = activities
+        .0
+        .iter()
+        .find(|act| act.source == "team")
+        .unwrap();
+    let id = team.id.clone().unwrap();
+    assert_eq!(id.source, "team");
+    assert_eq!(loader::source_path(&sources, &id).unwrap(), "tests/team");
+    assert!(loader::source_path(&sources[..1], &id).is_err());
+}
+
+#[test]
+fn test_write_source() {
+    let sources = [Source {
+        name: "team".to_string(),
+        path: "tests/team".to_string(),
+    }];
+    assert_eq!(
+        loader::write_source("tests/days", &[]),
+        Source {
+            name: "tests/days".to_string(),
+            path: "tests/days".to_string(),
+        }
+    );
+    assert_eq!(loader::write_source("tests/days", &sources), sources[0]);
+}